@@ -1,12 +1,15 @@
-use solana_program::pubkey::Pubkey;
-use solana_sdk::signature::Signer;
-use std::str::FromStr;
+use ore::{state::Proof, utils::AccountDeserialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_program::{native_token::lamports_to_sol, pubkey::Pubkey};
+use std::{io::Write, str::FromStr, sync::Arc, time::Duration};
 
-use crate::Miner;
+use crate::{
+    utils::{proof_pubkey, resolve_ata},
+    Miner,
+};
 
 impl Miner {
     pub async fn balance(&self, address: Option<String>) {
-        let signer = self.signer();
         let address = if let Some(address) = address {
             if let Ok(address) = Pubkey::from_str(&address) {
                 address
@@ -15,17 +18,12 @@ impl Miner {
                 return;
             }
         } else {
-            signer.pubkey()
+            let Some(address) = self.default_address() else {
+                return;
+            };
+            address
         };
-        let token_account_address = spl_associated_token_account::get_associated_token_address(
-            &address,
-            &ore::MINT_ADDRESS,
-        );
-        match self
-            .rpc_client
-            .get_token_account(&token_account_address)
-            .await
-        {
+        match resolve_ata(&self.rpc_client, address).await {
             Ok(token_account) => {
                 if let Some(token_account) = token_account {
                     println!("{:} ORE", token_account.token_amount.ui_amount_string);
@@ -39,3 +37,75 @@ impl Miner {
         }
     }
 }
+
+/// The SOL, ORE and claimable balances of an address. `None` marks a balance that failed to load.
+pub struct Balances {
+    pub sol: Option<f64>,
+    pub ore: Option<f64>,
+    pub claimable: Option<f64>,
+}
+
+impl Balances {
+    pub async fn fetch(rpc_client: &RpcClient, authority: Pubkey) -> Self {
+        let sol = rpc_client
+            .get_balance(&authority)
+            .await
+            .ok()
+            .map(lamports_to_sol);
+        let ore = resolve_ata(rpc_client, authority)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|token_account| token_account.token_amount.ui_amount);
+        let claimable = rpc_client
+            .get_account_data(&proof_pubkey(authority))
+            .await
+            .ok()
+            .and_then(|data| {
+                Proof::try_from_bytes(&data)
+                    .ok()
+                    .map(|proof| proof.claimable_rewards)
+            })
+            .map(|rewards| (rewards as f64) / 10f64.powf(ore::TOKEN_DECIMALS as f64));
+        Self {
+            sol,
+            ore,
+            claimable,
+        }
+    }
+
+    /// Prints the balances on one line. With `overwrite`, the cursor's line is replaced, so
+    /// only pass it when the previous line printed was this balance display.
+    pub fn print(&self, overwrite: bool) {
+        let format = |amount: Option<f64>| match amount {
+            Some(amount) => format!("{:.4}", amount),
+            None => "n/a".into(),
+        };
+        let line = format!(
+            "SOL: {} | ORE: {} | Claimable: {}",
+            format(self.sol),
+            format(self.ore),
+            format(self.claimable)
+        );
+        if overwrite {
+            print!("\r\x1b[2K{}", line);
+            std::io::stdout().flush().ok();
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Prints the SOL, ORE and claimable balances of `authority` every `interval_secs`
+/// (--balance-refresh-interval-secs). Mining logs are printed in between, so each
+/// report goes on its own line rather than overwriting the last one.
+pub async fn report_balances(
+    rpc_client: Arc<RpcClient>,
+    authority: Pubkey,
+    interval_secs: u64,
+) -> ! {
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+        Balances::fetch(&rpc_client, authority).await.print(false);
+    }
+}
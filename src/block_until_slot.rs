@@ -0,0 +1,34 @@
+use std::time::{Duration, Instant};
+
+use crate::Miner;
+
+const POLL_DELAY: u64 = 400;
+const REPORT_INTERVAL: u64 = 10;
+
+impl Miner {
+    pub async fn block_until_slot(&self, target_slot: u64) {
+        let mut last_report: Option<Instant> = None;
+        loop {
+            let slot = match self.rpc_client.get_slot().await {
+                Ok(slot) => slot,
+                Err(err) => {
+                    eprintln!("Failed to fetch slot: {:?}", err);
+                    std::process::exit(1);
+                }
+            };
+            if slot.ge(&target_slot) {
+                println!("Reached slot {}", slot);
+                return;
+            }
+            if last_report.is_none_or(|t| t.elapsed().as_secs().ge(&REPORT_INTERVAL)) {
+                println!(
+                    "Current slot: {} ({} slots remaining)",
+                    slot,
+                    target_slot - slot
+                );
+                last_report = Some(Instant::now());
+            }
+            tokio::time::sleep(Duration::from_millis(POLL_DELAY)).await;
+        }
+    }
+}
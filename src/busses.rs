@@ -1,23 +1,37 @@
-use ore::{state::Bus, utils::AccountDeserialize, BUS_ADDRESSES};
-use solana_client::client_error::Result;
+use ore::state::Bus;
+use solana_client::client_error::{ClientError, ClientErrorKind, Result};
 
-use crate::Miner;
+use crate::{
+    utils::{parse_account, print_raw_account, with_retries},
+    BussesArgs, Miner,
+};
 
 impl Miner {
-    pub async fn busses(&self) {
-        for address in BUS_ADDRESSES.iter() {
-            let data = self.rpc_client.get_account_data(address).await.unwrap();
-            match Bus::try_from_bytes(&data) {
-                Ok(bus) => {
-                    println!("Bus {}: {:} ORE", bus.id, bus.rewards);
+    pub async fn busses(&self, args: BussesArgs) {
+        for (id, address) in self.addresses.busses.iter().enumerate() {
+            let data = match with_retries(|| self.rpc_client.get_account_data(address)).await {
+                Ok(data) => data,
+                Err(err) => {
+                    println!("Bus {}: failed to fetch {}: {}", id, address, err);
+                    continue;
                 }
-                Err(_) => {}
+            };
+            match parse_account::<Bus>(&data) {
+                Ok(bus) => println!("Bus {}: {:} ORE", bus.id, bus.rewards),
+                Err(err) => println!("Bus {}: failed to parse {}: {}", id, address, err),
+            }
+            if args.raw_account {
+                print_raw_account(&data);
             }
         }
     }
 
     pub async fn get_bus(&self, id: usize) -> Result<Bus> {
-        let data = self.rpc_client.get_account_data(&BUS_ADDRESSES[id]).await?;
-        Ok(*Bus::try_from_bytes(&data).unwrap())
+        let data =
+            with_retries(|| self.rpc_client.get_account_data(&self.addresses.busses[id])).await?;
+        parse_account::<Bus>(&data).map_err(|err| ClientError {
+            request: None,
+            kind: ClientErrorKind::Custom(format!("Failed to parse bus {}: {}", id, err)),
+        })
     }
 }
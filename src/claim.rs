@@ -1,23 +1,77 @@
-use crate::utils::get_proof;
-use crate::{cu_limits::CU_LIMIT_CLAIM, Miner};
+use std::time::Duration;
+
+use crate::utils::{
+    get_proof_with_commitment, is_known_ata, ore_token_pubkey, proof_pubkey, remember_ata,
+    resolve_ata,
+};
+use crate::{cu_limits::CU_LIMIT_CLAIM, events::Event, ClaimArgs, Miner};
+use futures::StreamExt;
+use ore::{state::Proof, utils::AccountDeserialize};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{nonblocking::pubsub_client::PubsubClient, rpc_config::RpcAccountInfoConfig};
 use solana_program::pubkey::Pubkey;
-use solana_sdk::{compute_budget::ComputeBudgetInstruction, signature::Signer};
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction, signature::Signer,
+};
 
 impl Miner {
-    pub async fn claim(&self) {
-        let proof = get_proof(&self.rpc_client, self.signer().pubkey()).await;
-        let amount = proof.claimable_rewards;
+    pub async fn claim(&self, args: ClaimArgs) {
+        let mut proof = get_proof_with_commitment(
+            &self.rpc_client,
+            self.signer().pubkey(),
+            args.proof_commitment,
+        )
+        .await;
+        if args.wait_for_update {
+            if let Some(updated_proof) = self
+                .wait_for_proof_update(proof, args.wait_timeout_secs, args.proof_commitment)
+                .await
+            {
+                proof = updated_proof;
+            }
+        }
+        let amount = match args.percent {
+            Some(percent) => {
+                let amount = (proof.claimable_rewards as f64 * percent / 100.0).round() as u64;
+                println!(
+                    "{}% of claimable rewards: {} ORE",
+                    percent,
+                    (amount as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64))
+                );
+                amount
+            }
+            None => match args.amount {
+                Some(amount) if amount.gt(&proof.claimable_rewards) => {
+                    println!(
+                        "Cannot claim {} ORE, only {} ORE is claimable",
+                        (amount as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64)),
+                        (proof.claimable_rewards as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64))
+                    );
+                    return;
+                }
+                Some(amount) => amount,
+                None => proof.claimable_rewards,
+            },
+        };
+        self.claim_rewards(amount).await;
+    }
+
+    /// Claims `amount` of mining rewards to the signer's token account.
+    /// Returns whether the claim landed.
+    pub async fn claim_rewards(&self, amount: u64) -> bool {
         let amountf = (amount as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
         if amountf <= 0.0 {
             println!("nothing to claim, exit now.");
-            return;
+            return false;
         } else {
             println!("claimable rewards: {:} ORE", amountf);
         }
 
         let beneficiary = self.initialize_ata().await;
         let cu_limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(CU_LIMIT_CLAIM);
-        let cu_price_ix = ComputeBudgetInstruction::set_compute_unit_price(self.priority_fee);
+        let cu_price_ix =
+            ComputeBudgetInstruction::set_compute_unit_price(self.get_priority_fee().await);
         let ix = ore::instruction::claim(self.signer().pubkey(), beneficiary, amount);
         println!("Submitting claim transaction...");
         match self
@@ -26,10 +80,75 @@ impl Miner {
         {
             Ok(sig) => {
                 println!("Claimed {:} ORE to account {:}", amountf, beneficiary);
+                self.events.emit(Event::Claimed {
+                    amount,
+                    beneficiary: beneficiary.to_string(),
+                });
                 println!("{:?}", sig);
+                self.on_submission_succeeded();
+                true
             }
             Err(err) => {
                 println!("Error: {:?}", err);
+                self.on_submission_failed(&err.to_string());
+                false
+            }
+        }
+    }
+
+    /// Waits for the proof account's claimable rewards to increase, returning the updated proof.
+    /// Returns `None` if no increase was observed before the timeout.
+    async fn wait_for_proof_update(
+        &self,
+        proof: Proof,
+        timeout_secs: u64,
+        commitment: CommitmentConfig,
+    ) -> Option<Proof> {
+        let ws_url = solana_cli_config::Config::compute_websocket_url(&self.rpc_url);
+        let pubsub_client = match PubsubClient::new(&ws_url).await {
+            Ok(pubsub_client) => pubsub_client,
+            Err(err) => {
+                println!("Failed to connect to {}: {:?}", ws_url, err);
+                return None;
+            }
+        };
+        let (mut stream, _unsubscribe) = match pubsub_client
+            .account_subscribe(
+                &proof_pubkey(self.signer().pubkey()),
+                Some(RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(commitment),
+                    ..Default::default()
+                }),
+            )
+            .await
+        {
+            Ok(subscription) => subscription,
+            Err(err) => {
+                println!("Failed to subscribe to proof account: {:?}", err);
+                return None;
+            }
+        };
+
+        println!("Waiting for proof account update...");
+        let wait = async {
+            while let Some(res) = stream.next().await {
+                let Some(account) = res.value.decode::<Account>() else {
+                    continue;
+                };
+                if let Ok(updated_proof) = Proof::try_from_bytes(&account.data) {
+                    if updated_proof.claimable_rewards.gt(&proof.claimable_rewards) {
+                        return Some(*updated_proof);
+                    }
+                }
+            }
+            None
+        };
+        match tokio::time::timeout(Duration::from_secs(timeout_secs), wait).await {
+            Ok(updated_proof) => updated_proof,
+            Err(_) => {
+                println!("Timed out waiting for proof update, using current on-chain value");
+                None
             }
         }
     }
@@ -39,17 +158,13 @@ impl Miner {
         let signer = self.signer();
 
         // Build instructions.
-        let token_account_pubkey = spl_associated_token_account::get_associated_token_address(
-            &signer.pubkey(),
-            &ore::MINT_ADDRESS,
-        );
+        let token_account_pubkey = ore_token_pubkey(signer.pubkey());
 
         // Check if ata already exists
-        if let Ok(Some(_ata)) = self
-            .rpc_client
-            .get_token_account(&token_account_pubkey)
-            .await
-        {
+        if is_known_ata(signer.pubkey()) {
+            return token_account_pubkey;
+        }
+        if let Ok(Some(_ata)) = resolve_ata(&self.rpc_client, signer.pubkey()).await {
             return token_account_pubkey;
         }
 
@@ -62,7 +177,10 @@ impl Miner {
         );
         println!("Creating token account {}...", token_account_pubkey);
         match self.send_and_confirm(&[ix], true, false).await {
-            Ok(_sig) => println!("Created token account {:?}", token_account_pubkey),
+            Ok(_sig) => {
+                println!("Created token account {:?}", token_account_pubkey);
+                remember_ata(signer.pubkey());
+            }
             Err(e) => println!("Transaction failed: {:?}", e),
         }
 
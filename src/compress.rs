@@ -0,0 +1,130 @@
+use std::{sync::atomic::Ordering, time::Instant};
+
+use base64::Engine;
+use reqwest::{header, StatusCode};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    rpc_config::RpcSendTransactionConfig,
+};
+use solana_sdk::{signature::Signature, transaction::Transaction};
+use std::str::FromStr;
+
+use crate::{rpc_log, Miner};
+
+// Print the running compression ratio every this many submissions
+const COMPRESSION_REPORT_INTERVAL: u64 = 10;
+
+impl Miner {
+    /// Submits a transaction with a zstd-compressed request body.
+    /// Returns `None` if the endpoint does not accept compressed requests, in which case
+    /// compression is disabled for the rest of the session and the caller should fall back.
+    pub async fn send_transaction_compressed(
+        &self,
+        tx: &Transaction,
+        send_cfg: RpcSendTransactionConfig,
+    ) -> Option<ClientResult<Signature>> {
+        if !self.compress_txs.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        // Build request
+        let serialized_tx = bincode::serialize(tx).ok()?;
+        let encoded_tx = base64::engine::general_purpose::STANDARD.encode(serialized_tx);
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "sendTransaction",
+            "params": [encoded_tx, send_cfg],
+        })
+        .to_string();
+        let compressed_body = match zstd::encode_all(body.as_bytes(), 0) {
+            Ok(compressed_body) => compressed_body,
+            Err(err) => {
+                println!("Failed to compress transaction: {:?}", err);
+                return None;
+            }
+        };
+        self.record_compression(body.len(), compressed_body.len());
+
+        // Submit request, under the same --max-concurrent-rpcs limit and --log-rpc-calls logging
+        // as the RPC client
+        let permit = self.rpc_permits.acquire().await.unwrap();
+        let requested_at = chrono::Utc::now();
+        let started_at = Instant::now();
+        let res = self.post_compressed(compressed_body).await;
+        drop(permit);
+        if self.log_rpc_calls {
+            rpc_log::log_rpc_call(
+                "sendTransaction (zstd)",
+                requested_at,
+                started_at,
+                res.as_ref().map_or(0, |(_, body)| body.len()),
+                res.is_err(),
+            );
+        }
+        let (status, body) = match res {
+            Ok(res) => res,
+            Err(err) => return Some(Err(custom_error(err.to_string()))),
+        };
+        if status.eq(&StatusCode::UNSUPPORTED_MEDIA_TYPE) {
+            println!("RPC does not support compressed transactions, sending uncompressed");
+            self.compress_txs.store(false, Ordering::Relaxed);
+            return None;
+        }
+
+        // Parse response
+        let res = match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(res) => res,
+            Err(err) => return Some(Err(custom_error(err.to_string()))),
+        };
+        if let Some(err) = res.get("error") {
+            return Some(Err(custom_error(err.to_string())));
+        }
+        Some(
+            res.get("result")
+                .and_then(|sig| sig.as_str())
+                .and_then(|sig| Signature::from_str(sig).ok())
+                .ok_or_else(|| custom_error(format!("Unexpected response: {}", res))),
+        )
+    }
+
+    /// Posts a compressed request body to the RPC, returning the response status and body.
+    async fn post_compressed(
+        &self,
+        compressed_body: Vec<u8>,
+    ) -> reqwest::Result<(StatusCode, String)> {
+        let res = self
+            .http_client
+            .post(&self.rpc_url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_ENCODING, "zstd")
+            .body(compressed_body)
+            .send()
+            .await?;
+        let status = res.status();
+        Ok((status, res.text().await?))
+    }
+
+    fn record_compression(&self, raw_len: usize, compressed_len: usize) {
+        let mut lock = self.compression_stats.lock().unwrap();
+        lock.0 += raw_len as u64;
+        lock.1 += compressed_len as u64;
+        lock.2 += 1;
+        if lock.2 % COMPRESSION_REPORT_INTERVAL == 1 {
+            println!(
+                "Compression ratio: {:.2} ({} -> {} bytes over {} txs)",
+                lock.0 as f64 / lock.1.max(1) as f64,
+                lock.0,
+                lock.1,
+                lock.2
+            );
+        }
+    }
+}
+
+fn custom_error(msg: String) -> ClientError {
+    ClientError {
+        request: None,
+        kind: ClientErrorKind::Custom(msg),
+    }
+}
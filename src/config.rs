@@ -0,0 +1,143 @@
+use clap::{parser::ValueSource, ArgMatches};
+use serde::Serialize;
+use solana_sdk::signature::{read_keypair_file, Signer};
+
+use crate::utils::OutputFormat;
+
+#[derive(Debug, Serialize)]
+struct ConfigEntry {
+    setting: &'static str,
+    value: String,
+    source: String,
+}
+
+/// Prints the settings in effect after merging command line flags, the Solana config file and defaults.
+pub fn dump_config(
+    matches: &ArgMatches,
+    cli_config_source: &str,
+    rpc_url: &str,
+    keypair_path: &str,
+    keypair_env: Option<&str>,
+    format: OutputFormat,
+) {
+    // Settings that fall back to the Solana config file
+    let config_source = |id: &str, flag: &str| match matches.value_source(id) {
+        Some(ValueSource::CommandLine) => flag.to_string(),
+        _ => cli_config_source.to_string(),
+    };
+
+    // Settings that fall back to a built-in default
+    let arg_source = |id: &str, flag: &str| match matches.value_source(id) {
+        Some(ValueSource::CommandLine) => flag.to_string(),
+        Some(ValueSource::EnvVariable) => "environment".to_string(),
+        _ => "default".to_string(),
+    };
+    let arg_value = |id: &str| {
+        matches
+            .get_raw(id)
+            .and_then(|mut values| values.next())
+            .map(|value| value.to_string_lossy().to_string())
+            .unwrap_or_else(|| "none".to_string())
+    };
+
+    // --keypair-env takes precedence over the keypair path when loading the signer
+    let (keypair_setting, keypair_value, keypair_source) = match keypair_env {
+        Some(var) => (
+            "keypair_env",
+            format!("${}", var),
+            "--keypair-env".to_string(),
+        ),
+        None => (
+            "keypair_path",
+            keypair_path.to_string(),
+            config_source("keypair", "--keypair"),
+        ),
+    };
+    let pubkey = match keypair_env {
+        Some(var) => match crate::read_keypair_env(var) {
+            Ok(keypair) => keypair.pubkey().to_string(),
+            Err(err) => format!("unreadable ({})", err),
+        },
+        None if keypair_path.eq("-") => "read from stdin".to_string(),
+        None => match read_keypair_file(keypair_path) {
+            Ok(keypair) => keypair.pubkey().to_string(),
+            Err(err) => format!("unreadable ({})", err),
+        },
+    };
+    let entries = vec![
+        ConfigEntry {
+            setting: "rpc_url",
+            value: rpc_url.to_string(),
+            source: config_source("rpc", "--rpc"),
+        },
+        ConfigEntry {
+            setting: keypair_setting,
+            value: keypair_value,
+            source: keypair_source,
+        },
+        ConfigEntry {
+            setting: "pubkey",
+            value: pubkey,
+            source: keypair_setting.to_string(),
+        },
+        ConfigEntry {
+            setting: "commitment",
+            value: "confirmed".to_string(),
+            source: "built-in".to_string(),
+        },
+        ConfigEntry {
+            setting: "priority_fee",
+            value: arg_value("priority_fee"),
+            source: arg_source("priority_fee", "--priority-fee"),
+        },
+        ConfigEntry {
+            setting: "fee_percentile",
+            value: arg_value("fee_percentile"),
+            source: arg_source("fee_percentile", "--fee-percentile"),
+        },
+        ConfigEntry {
+            setting: "min_priority_fee",
+            value: arg_value("min_priority_fee"),
+            source: arg_source("min_priority_fee", "--min-priority-fee"),
+        },
+        ConfigEntry {
+            setting: "confirm_poll_interval_ms",
+            value: arg_value("confirm_poll_interval_ms"),
+            source: arg_source("confirm_poll_interval_ms", "--confirm-poll-interval-ms"),
+        },
+        ConfigEntry {
+            setting: "threads",
+            value: "1".to_string(),
+            source: "default (mine --threads)".to_string(),
+        },
+    ];
+
+    match format {
+        OutputFormat::Text => {
+            for entry in entries {
+                println!("{}: {} ({})", entry.setting, entry.value, entry.source);
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_entry_json_shape() {
+        let entry = ConfigEntry {
+            setting: "rpc_url",
+            value: "http://localhost:8899".into(),
+            source: "--rpc".into(),
+        };
+        assert_eq!(
+            serde_json::to_string(&entry).unwrap(),
+            r#"{"setting":"rpc_url","value":"http://localhost:8899","source":"--rpc"}"#
+        );
+    }
+}
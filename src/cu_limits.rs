@@ -1,3 +1,34 @@
+use solana_program::instruction::Instruction;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+
 pub const CU_LIMIT_CLAIM: u32 = 11_000;
 pub const CU_LIMIT_RESET: u32 = 12_200;
 pub const CU_LIMIT_MINE: u32 = 3200;
+pub const CU_LIMIT_REGISTER: u32 = 10_000;
+
+pub const MIN_HEAP_FRAME_BYTES: u32 = 32 * 1024;
+pub const MAX_HEAP_FRAME_BYTES: u32 = 256 * 1024;
+
+/// Returns the ORE instruction discriminator of a transaction's instructions, used to
+/// track compute usage per instruction type (--cu-margin).
+pub fn ore_instruction_kind(ixs: &[Instruction]) -> Option<u8> {
+    ixs.iter()
+        .find(|ix| ix.program_id.eq(&ore::ID))
+        .and_then(|ix| ix.data.first().copied())
+}
+
+/// Replaces the compute unit limit instruction, if any, with one requesting `cu_limit`.
+pub fn resize_cu_limit(ixs: &[Instruction], cu_limit: u32) -> Vec<Instruction> {
+    let cu_limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(cu_limit);
+    ixs.iter()
+        .map(|ix| {
+            if ix.program_id.eq(&cu_limit_ix.program_id)
+                && ix.data.first().eq(&cu_limit_ix.data.first())
+            {
+                cu_limit_ix.clone()
+            } else {
+                ix.clone()
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,63 @@
+use ore::EPOCH_DURATION;
+use serde::Serialize;
+
+use crate::{
+    utils::{get_clock_account, get_treasury, OutputFormat},
+    Miner,
+};
+
+#[derive(Debug, Serialize)]
+struct EpochStatus {
+    last_reset_at: i64,
+    epoch_duration_secs: i64,
+    secs_since_reset: i64,
+    secs_until_reset: i64,
+}
+
+impl Miner {
+    pub async fn epoch(&self, format: OutputFormat) {
+        let treasury = get_treasury(&self.rpc_client, &self.addresses.treasury).await;
+        let clock = get_clock_account(&self.rpc_client).await;
+        let secs_since_reset = clock.unix_timestamp.saturating_sub(treasury.last_reset_at);
+        let status = EpochStatus {
+            last_reset_at: treasury.last_reset_at,
+            epoch_duration_secs: EPOCH_DURATION,
+            secs_since_reset,
+            secs_until_reset: EPOCH_DURATION.saturating_sub(secs_since_reset).max(0),
+        };
+        match format {
+            OutputFormat::Text => {
+                println!("Last reset at: {}", status.last_reset_at);
+                println!("Time since reset: {}s", status.secs_since_reset);
+                println!("Reset interval: {}s", status.epoch_duration_secs);
+                if status.secs_until_reset.eq(&0) {
+                    println!("Next reset: due now");
+                } else {
+                    println!("Next reset: in ~{}s", status.secs_until_reset);
+                }
+            }
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string(&status).unwrap());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoch_status_json_shape() {
+        let status = EpochStatus {
+            last_reset_at: 100,
+            epoch_duration_secs: 60,
+            secs_since_reset: 45,
+            secs_until_reset: 15,
+        };
+        assert_eq!(
+            serde_json::to_string(&status).unwrap(),
+            r#"{"last_reset_at":100,"epoch_duration_secs":60,"secs_since_reset":45,"secs_until_reset":15}"#
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use std::{fs::File, io::Write, sync::Mutex};
+
+use serde::Serialize;
+
+use crate::stats::MiningReport;
+
+/// Structured events written to the `--events-file` as newline-delimited JSON,
+/// independent of the human-readable terminal output.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    SolutionFound { hash: String, nonce: u64 },
+    RoundSkipped { reason: String },
+    TransactionLanded { signature: String },
+    TransactionFailed { error: String },
+    Claimed { amount: u64, beneficiary: String },
+    Report(MiningReport),
+}
+
+#[derive(Serialize)]
+struct EventRecord<'a> {
+    timestamp: i64,
+    tag: &'a str,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+pub struct EventSink {
+    file: Option<Mutex<File>>,
+    tag: String,
+}
+
+impl EventSink {
+    pub fn new(path: Option<&str>, tag: String) -> std::io::Result<Self> {
+        let file = match path {
+            Some(path) => Some(Mutex::new(
+                File::options().create(true).append(true).open(path)?,
+            )),
+            None => None,
+        };
+        Ok(Self { file, tag })
+    }
+
+    pub fn emit(&self, event: Event) {
+        let Some(file) = self.file.as_ref() else {
+            return;
+        };
+        let record = EventRecord {
+            timestamp: chrono::Utc::now().timestamp(),
+            tag: &self.tag,
+            event: &event,
+        };
+        let mut line = serde_json::to_string(&record).unwrap();
+        line.push('\n');
+        let mut file = file.lock().unwrap();
+        if let Err(err) = file.write_all(line.as_bytes()) {
+            println!("Failed to write event: {:?}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_record_json_shape() {
+        let event = Event::Claimed {
+            amount: 1,
+            beneficiary: "beneficiary".into(),
+        };
+        let record = EventRecord {
+            timestamp: 2,
+            tag: "tag",
+            event: &event,
+        };
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"timestamp":2,"tag":"tag","event":"claimed","amount":1,"beneficiary":"beneficiary"}"#
+        );
+    }
+
+    #[test]
+    fn event_json_shape() {
+        let event = Event::TransactionFailed {
+            error: "error".into(),
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"transaction_failed","error":"error"}"#
+        );
+    }
+}
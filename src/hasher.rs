@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use clap::ValueEnum;
+use sha2::Digest;
+
+/// A hash function the mining loop searches nonces with (--hash-algorithm).
+pub trait Hasher: Send + Sync {
+    fn hash(&self, data: &[u8]) -> [u8; 32];
+}
+
+pub struct Keccak256;
+pub struct Sha256;
+pub struct Sha3_256;
+pub struct Blake3;
+
+impl Hasher for Keccak256 {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        solana_sdk::keccak::hash(data).to_bytes()
+    }
+}
+
+impl Hasher for Sha256 {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        sha2::Sha256::digest(data).into()
+    }
+}
+
+impl Hasher for Sha3_256 {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        sha3::Sha3_256::digest(data).into()
+    }
+}
+
+impl Hasher for Blake3 {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        blake3::hash(data).into()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum HashAlgorithm {
+    /// The hash ore-program verifies solutions with
+    #[default]
+    Keccak256,
+    Sha256,
+    #[value(name = "sha3-256")]
+    Sha3_256,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    pub fn hasher(self) -> Arc<dyn Hasher> {
+        match self {
+            HashAlgorithm::Keccak256 => Arc::new(Keccak256),
+            HashAlgorithm::Sha256 => Arc::new(Sha256),
+            HashAlgorithm::Sha3_256 => Arc::new(Sha3_256),
+            HashAlgorithm::Blake3 => Arc::new(Blake3),
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use std::str::FromStr;
+
+use ore::instruction::OreInstruction;
+use serde::Serialize;
+use solana_client::{
+    rpc_client::GetConfirmedSignaturesForAddress2Config, rpc_config::RpcTransactionConfig,
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::{stats::ReportFormat, utils::proof_pubkey, HistoryArgs, Miner};
+
+// Signatures to request per page when scanning for a slot range
+const SIGNATURES_PAGE_SIZE: usize = 1000;
+
+/// The epoch to list history for, given as a number or `current`.
+#[derive(Clone, Copy, Debug)]
+pub enum TargetEpoch {
+    Current,
+    Epoch(u64),
+}
+
+impl FromStr for TargetEpoch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("current") {
+            return Ok(TargetEpoch::Current);
+        }
+        s.parse()
+            .map(TargetEpoch::Epoch)
+            .map_err(|_| format!("expected an epoch number or \"current\", got {:?}", s))
+    }
+}
+
+/// An ore instruction in the history of an account, as printed by `history`.
+#[derive(Debug, Serialize)]
+pub struct HistoryEntry {
+    pub block_time: Option<i64>,
+    pub slot: u64,
+    pub signature: String,
+    pub instruction: String,
+    pub amount: Option<u64>,
+    pub failed: bool,
+}
+
+impl HistoryEntry {
+    pub const CSV_HEADER: &'static str = "block_time,slot,signature,instruction,amount,failed";
+
+    pub fn print(&self, format: ReportFormat, first: bool) {
+        match format {
+            ReportFormat::Text => {
+                let time = self
+                    .block_time
+                    .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+                    .map(|t| t.to_rfc3339())
+                    .unwrap_or_else(|| "unknown".into());
+                let activity = match self.amount {
+                    Some(amount) => format!(
+                        "{} {} ORE",
+                        self.instruction,
+                        (amount as f64) / 10f64.powf(ore::TOKEN_DECIMALS as f64)
+                    ),
+                    None => self.instruction.clone(),
+                };
+                let result = if self.failed { " (failed)" } else { "" };
+                println!("{} {} {}{}", time, self.signature, activity, result);
+            }
+            ReportFormat::Json => {
+                println!("{}", serde_json::to_string(self).unwrap());
+            }
+            ReportFormat::Csv => {
+                if first {
+                    println!("{}", Self::CSV_HEADER);
+                }
+                println!(
+                    "{},{},{},{},{},{}",
+                    self.block_time.map(|t| t.to_string()).unwrap_or_default(),
+                    self.slot,
+                    self.signature,
+                    self.instruction,
+                    self.amount.map(|a| a.to_string()).unwrap_or_default(),
+                    self.failed
+                );
+            }
+        }
+    }
+}
+
+/// Bounds on the transactions listed by `history`, from --from-slot, --until-slot, --since and --target-epoch.
+struct HistoryRange {
+    first_slot: Option<u64>,
+    last_slot: Option<u64>,
+    since: Option<i64>,
+}
+
+impl HistoryRange {
+    fn is_unbounded(&self) -> bool {
+        self.first_slot.is_none() && self.last_slot.is_none() && self.since.is_none()
+    }
+
+    fn contains(&self, status: &RpcConfirmedTransactionStatusWithSignature) -> bool {
+        self.first_slot.is_none_or(|slot| status.slot.ge(&slot))
+            && self.last_slot.is_none_or(|slot| status.slot.le(&slot))
+            && self
+                .since
+                .is_none_or(|since| status.block_time.is_some_and(|t| t.ge(&since)))
+    }
+
+    /// Returns whether `status` is older than the start of the range, so no older signature can match.
+    fn is_past(&self, status: &RpcConfirmedTransactionStatusWithSignature) -> bool {
+        self.first_slot.is_some_and(|slot| status.slot.lt(&slot))
+            || self
+                .since
+                .is_some_and(|since| status.block_time.is_some_and(|t| t.lt(&since)))
+    }
+}
+
+impl Miner {
+    pub async fn history(&self, args: HistoryArgs) {
+        let address = if let Some(address) = args.address {
+            if let Ok(address) = Pubkey::from_str(&address) {
+                address
+            } else {
+                println!("Invalid address: {:?}", address);
+                return;
+            }
+        } else {
+            let Some(address) = self.default_address() else {
+                return;
+            };
+            address
+        };
+
+        // Restrict to the requested range, narrowed to the slots of --target-epoch if set
+        let mut range = HistoryRange {
+            first_slot: args.from_slot,
+            last_slot: args.until_slot,
+            since: args.since,
+        };
+        if let Some(target_epoch) = args.target_epoch {
+            let (epoch, first_slot, last_slot) = match self.epoch_slot_range(target_epoch).await {
+                Ok(range) => range,
+                Err(err) => {
+                    println!("Failed to fetch epoch schedule: {:?}", err);
+                    return;
+                }
+            };
+            if let ReportFormat::Text = args.format {
+                println!("Epoch {}: slots {} to {}", epoch, first_slot, last_slot);
+            }
+            range.first_slot = Some(range.first_slot.map_or(first_slot, |s| s.max(first_slot)));
+            range.last_slot = Some(range.last_slot.map_or(last_slot, |s| s.min(last_slot)));
+        }
+
+        // Every mine and claim writes to the proof account, so its history is the activity log
+        let signatures = match self.get_proof_signatures(address, args.limit, &range).await {
+            Ok(signatures) => signatures,
+            Err(err) => {
+                println!("Failed to fetch signatures: {:?}", err);
+                return;
+            }
+        };
+
+        let mut first = true;
+        for status in signatures {
+            let Ok(signature) = Signature::from_str(&status.signature) else {
+                continue;
+            };
+            for (instruction, amount) in self.decode_ore_activity(&signature).await {
+                HistoryEntry {
+                    block_time: status.block_time,
+                    slot: status.slot,
+                    signature: status.signature.clone(),
+                    instruction,
+                    amount,
+                    failed: status.err.is_some(),
+                }
+                .print(args.format, first);
+                first = false;
+            }
+        }
+    }
+
+    /// Returns the epoch number and its first and last slots.
+    async fn epoch_slot_range(
+        &self,
+        target_epoch: TargetEpoch,
+    ) -> solana_client::client_error::Result<(u64, u64, u64)> {
+        let epoch = match target_epoch {
+            TargetEpoch::Current => self.rpc_client.get_epoch_info().await?.epoch,
+            TargetEpoch::Epoch(epoch) => epoch,
+        };
+        let schedule = self.rpc_client.get_epoch_schedule().await?;
+        Ok((
+            epoch,
+            schedule.get_first_slot_in_epoch(epoch),
+            schedule.get_last_slot_in_epoch(epoch),
+        ))
+    }
+
+    /// Fetches up to `limit` of the most recent signatures on the proof account of `address`.
+    /// With a bounded range, pages back through history until passing its start.
+    async fn get_proof_signatures(
+        &self,
+        address: Pubkey,
+        limit: usize,
+        range: &HistoryRange,
+    ) -> solana_client::client_error::Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        if range.is_unbounded() {
+            return self
+                .rpc_client
+                .get_signatures_for_address_with_config(
+                    &proof_pubkey(address),
+                    GetConfirmedSignaturesForAddress2Config {
+                        limit: Some(limit),
+                        ..Default::default()
+                    },
+                )
+                .await;
+        }
+        let mut signatures = vec![];
+        let mut before = None;
+        loop {
+            let page = self
+                .rpc_client
+                .get_signatures_for_address_with_config(
+                    &proof_pubkey(address),
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        limit: Some(SIGNATURES_PAGE_SIZE),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            let Some(last) = page.last() else {
+                return Ok(signatures);
+            };
+            let done = range.is_past(last) || page.len().lt(&SIGNATURES_PAGE_SIZE);
+            before = Signature::from_str(&last.signature).ok();
+            for status in page {
+                if range.contains(&status) {
+                    signatures.push(status);
+                    if signatures.len().ge(&limit) {
+                        return Ok(signatures);
+                    }
+                }
+            }
+            if done || before.is_none() {
+                return Ok(signatures);
+            }
+        }
+    }
+
+    /// Returns the name of each ore instruction in the transaction, with the amount of claims.
+    async fn decode_ore_activity(&self, signature: &Signature) -> Vec<(String, Option<u64>)> {
+        let tx = match self
+            .rpc_client
+            .get_transaction_with_config(
+                signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: None,
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await
+        {
+            Ok(tx) => tx,
+            Err(err) => {
+                println!("Failed to fetch transaction {}: {:?}", signature, err);
+                return vec![];
+            }
+        };
+        let Some(tx) = tx.transaction.transaction.decode() else {
+            return vec![];
+        };
+        let account_keys = tx.message.static_account_keys();
+        tx.message
+            .instructions()
+            .iter()
+            .filter(|ix| {
+                account_keys
+                    .get(ix.program_id_index as usize)
+                    .eq(&Some(&ore::ID))
+            })
+            .filter_map(|ix| {
+                let kind = OreInstruction::try_from(*ix.data.first()?).ok()?;
+                let amount = match kind {
+                    OreInstruction::Claim => {
+                        Some(u64::from_le_bytes(ix.data.get(1..9)?.try_into().ok()?))
+                    }
+                    _ => None,
+                };
+                Some((format!("{:?}", kind).to_lowercase(), amount))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_entry_json_shape() {
+        let entry = HistoryEntry {
+            block_time: Some(1),
+            slot: 2,
+            signature: "sig".into(),
+            instruction: "claim".into(),
+            amount: Some(3),
+            failed: false,
+        };
+        assert_eq!(
+            serde_json::to_string(&entry).unwrap(),
+            r#"{"block_time":1,"slot":2,"signature":"sig","instruction":"claim","amount":3,"failed":false}"#
+        );
+    }
+}
@@ -1,144 +1,1122 @@
 mod balance;
+mod block_until_slot;
 mod busses;
 mod claim;
+mod compress;
+mod config;
 mod cu_limits;
+mod epoch;
+mod events;
+mod hasher;
+mod history;
 #[cfg(feature = "admin")]
 mod initialize;
+mod metrics;
 mod mine;
+mod observe;
+mod offline;
+mod power;
+mod priority_fee;
+mod recover;
 mod register;
 mod rewards;
+mod rpc_log;
 mod send_and_confirm;
+mod show_accounts;
+mod snapshot;
+mod snapshot_balance;
+mod solution_sink;
+mod stats;
 mod treasury;
 #[cfg(feature = "admin")]
 mod update_admin;
 #[cfg(feature = "admin")]
 mod update_difficulty;
 mod utils;
+mod verify;
+mod webhook;
+mod wsol;
 
 use anyhow::Result;
-use clap::{command, Parser, Subcommand};
-use solana_client::nonblocking::rpc_client::RpcClient;
+use clap::{error::ErrorKind, CommandFactory, FromArgMatches, Parser, Subcommand};
+use cu_limits::{MAX_HEAP_FRAME_BYTES, MIN_HEAP_FRAME_BYTES};
+use events::EventSink;
+use metrics::InfluxDbConfig;
+use ore::{state::Treasury, utils::AccountDeserialize, BUS_COUNT};
+use priority_fee::FeeUnit;
+use solana_client::{client_error::Result as ClientResult, nonblocking::rpc_client::RpcClient};
+use solana_program::pubkey::Pubkey;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     hash::Hash,
-    signature::{read_keypair_file, Keypair},
+    signature::{read_keypair_file, Keypair, Signer},
 };
+use stats::{ReportFormat, SessionStats};
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::RwLock;
+use std::time::{Instant, SystemTime};
+use tokio::sync::{OnceCell, Semaphore};
+use utils::{OutputFormat, ProgramAddresses};
+
+// Clock skew from the cluster beyond which to warn on startup
+const MAX_CLOCK_SKEW_SECS: i64 = 5;
+
+// Consecutive failed RPC health checks before warning that the RPC may be down
+const KEEPALIVE_MAX_FAILURES: usize = 3;
+
+// Version of the ore program whose account layouts and CU limits this CLI targets
+const ORE_PROGRAM_VERSION: &str = "1.2.1";
+
+// Exit code when --max-consecutive-failures is reached
+const EXIT_CONSECUTIVE_FAILURES: i32 = 3;
 
 pub struct Miner {
-    pub keypair: Keypair,
+    pub keypair: Option<Arc<RwLock<Arc<Keypair>>>>,
     pub priority_fee: u64,
+    pub priority_fee_decay: Option<u64>,
+    pub priority_fee_increase_on_fail: f64,
+    pub max_priority_fee_cap: Option<u64>,
+    pub auto_priority_fee_bump_secs: Option<u64>,
+    pub auto_priority_fee_bump_amount: u64,
+    current_priority_fee: Mutex<(u64, Instant)>,
+    pub fee_percentile: Option<u8>,
+    pub fee_unit: FeeUnit,
+    pub min_priority_fee: u64,
+    percentile_fee_cache: Mutex<Option<(u64, Instant)>>,
+    pub fee_oracle: Option<String>,
+    priority_fee_account: Option<Pubkey>,
+    oracle_fee_cache: Mutex<Option<(u64, Instant)>>,
+    pub compress_txs: AtomicBool,
+    pub simulate_before_submit: bool,
+    pub sign_only: bool,
+    pub unsigned_tx_output: Option<String>,
+    compression_stats: Mutex<(u64, u64, u64)>,
+    pub session_stats: Arc<SessionStats>,
+    pub events: Arc<EventSink>,
+    pub notify_webhook: Option<String>,
+    pub tag: String,
+    pub log_prefix: String,
+    pub rpc_send_retries: Option<usize>,
+    pub heap_bytes: Option<u32>,
+    pub cu_margin: Option<u64>,
+    observed_cus: Mutex<HashMap<u8, u64>>,
+    pub fast_exit: bool,
+    pub confirm_poll_interval_ms: u64,
+    pub resubmit_interval_ms: Option<u64>,
+    pub wait_for_finality: bool,
+    pub retry_on_blockhash_not_found: bool,
+    max_consecutive_failures: Option<u64>,
+    consecutive_failures: AtomicU64,
+    pub wallet_permits: Semaphore,
+    shutdown: Arc<AtomicBool>,
+    pub influxdb: Option<InfluxDbConfig>,
+
+    pub addresses: ProgramAddresses,
 
     pub rpc_url: String,
-    pub rpc_client: RpcClient,
+    pub rpc_client: Arc<RpcClient>,
+    broadcast_clients: Vec<Arc<RpcClient>>,
+    log_rpc_calls: bool,
+    connect_timeout_ms: u64,
+    rpc_permits: Arc<Semaphore>,
+    pub http_client: reqwest::Client,
     latest_blockhash: Arc<Mutex<(Hash, u64)>>,
+    blockhash_poller: OnceCell<()>,
+    stall_detection_secs: u64,
+}
+
+#[derive(Parser, Debug)]
+#[command(about, version)]
+pub struct Args {
+    #[arg(
+        long,
+        value_name = "NETWORK_URL",
+        help = "Network address of your RPC provider",
+        global = true
+    )]
+    rpc: Option<String>,
+
+    #[clap(
+        global = true,
+        short = 'C',
+        long = "config",
+        id = "PATH",
+        help = "Filepath to config file."
+    )]
+    pub config_file: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "KEYPAIR_FILEPATH",
+        help = "Filepath to keypair to use, or - to read it from stdin",
+        global = true
+    )]
+    keypair: Option<String>,
+
+    #[arg(
+        long,
+        help = "Reload the keypair whenever the keypair file changes on disk",
+        global = true
+    )]
+    keypair_watch: bool,
+
+    #[arg(
+        long,
+        value_name = "VAR",
+        help = "Read the keypair from this environment variable, as a JSON byte array or base58 private key",
+        conflicts_with_all = ["keypair", "keypair_watch"],
+        global = true
+    )]
+    keypair_env: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Display this name instead of the keypair's pubkey in log output",
+        global = true
+    )]
+    keypair_alias: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "MICROLAMPORTS",
+        help = "Priority fee in microlamports, per compute unit or per transaction as set by --fee-unit",
+        default_value = "0",
+        global = true
+    )]
+    priority_fee: u64,
+
+    #[arg(
+        long,
+        value_name = "HALF_LIFE_SECS",
+        help = "Halve the priority fee every HALF_LIFE_SECS while transactions keep landing, down to --min-priority-fee",
+        conflicts_with_all = ["fee_percentile", "fee_oracle"],
+        global = true
+    )]
+    priority_fee_decay: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "MULTIPLIER",
+        help = "Multiply the priority fee by MULTIPLIER after each failed landing, resetting it once a transaction lands",
+        default_value = "1.0",
+        conflicts_with_all = ["fee_percentile", "fee_oracle"],
+        global = true
+    )]
+    priority_fee_increase_on_fail: f64,
+
+    #[arg(
+        long,
+        value_name = "MICROLAMPORTS",
+        help = "Upper bound for priority fees raised by --priority-fee-increase-on-fail or --auto-priority-fee-bump-secs",
+        global = true
+    )]
+    max_priority_fee_cap: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Resubmit with a higher priority fee if a transaction is not confirmed within SECONDS of its first submission, and again every SECONDS after",
+        global = true
+    )]
+    auto_priority_fee_bump_secs: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "MICROLAMPORTS",
+        help = "Amount to raise the priority fee by on each --auto-priority-fee-bump-secs resubmission",
+        default_value = "10000",
+        global = true
+    )]
+    auto_priority_fee_bump_amount: u64,
+
+    #[arg(
+        long,
+        value_name = "PERCENTILE",
+        help = "Set the priority fee to this percentile of recent cluster prioritization fees",
+        value_parser = clap::value_parser!(u8).range(0..=100),
+        global = true
+    )]
+    fee_percentile: Option<u8>,
+
+    #[arg(
+        long,
+        value_name = "UNIT",
+        help = "Whether --priority-fee, --min-priority-fee, --max-priority-fee-cap and fee bumps are in microlamports per compute unit or per transaction",
+        default_value = "per-cu",
+        conflicts_with_all = ["fee_percentile", "fee_oracle"],
+        global = true
+    )]
+    fee_unit: FeeUnit,
+
+    #[arg(
+        long,
+        value_name = "MICROLAMPORTS",
+        help = "Minimum priority fee to pay when using --fee-percentile or --fee-oracle, and the floor for --priority-fee-decay",
+        default_value = "0",
+        global = true
+    )]
+    min_priority_fee: u64,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Use the fee recommended by this getPriorityFeeEstimate endpoint (e.g. Helius), falling back to --fee-percentile or --priority-fee",
+        global = true
+    )]
+    fee_oracle: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PUBKEY",
+        help = "Base --fee-percentile and --fee-oracle estimates on the recent fees of this account instead of the busses",
+        global = true
+    )]
+    use_priority_fee_account: Option<Pubkey>,
+
+    #[arg(
+        long,
+        help = "Submit transactions with zstd-compressed request bodies, if the RPC supports it",
+        global = true
+    )]
+    compress_txs: bool,
+
+    #[arg(
+        long,
+        help = "Simulate every transaction before submitting it, and skip submission if the simulation fails",
+        global = true
+    )]
+    simulate_before_submit: bool,
+
+    #[arg(
+        long,
+        help = "Sign the first transaction and print it as base64 instead of submitting it, then exit",
+        global = true
+    )]
+    sign_only: bool,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the transaction signed with --sign-only to this file instead of printing it",
+        requires = "sign_only",
+        global = true
+    )]
+    unsigned_tx_output: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Submit a base64 transaction written by --sign-only, instead of running a command"
+    )]
+    submit_signed_tx: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Append structured events to this file as newline-delimited JSON",
+        global = true
+    )]
+    events_file: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Webhook URL to post alerts to",
+        global = true
+    )]
+    notify_webhook: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "LABEL",
+        help = "Label attached to events, metrics and alerts from this instance [default: the hostname]. When set, mining status lines are prefixed with [LABEL]",
+        global = true
+    )]
+    tag: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Number of times the RPC node rebroadcasts each submitted transaction [default: the RPC's default]",
+        global = true
+    )]
+    rpc_send_retries: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Reconnect to the RPC if the latest blockhash has not changed in SECONDS",
+        default_value = "60",
+        global = true
+    )]
+    stall_detection_secs: u64,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Check the RPC's health every SECONDS to keep the connection alive. 0 disables the check",
+        default_value = "30",
+        global = true
+    )]
+    rpc_keepalive_secs: u64,
+
+    #[arg(
+        long,
+        value_name = "MILLISECONDS",
+        help = "How long to wait between transaction confirmation checks",
+        default_value = "5000",
+        global = true
+    )]
+    confirm_poll_interval_ms: u64,
+
+    #[arg(
+        long,
+        value_name = "MILLISECONDS",
+        help = "Resubmit each pending transaction every MILLISECONDS until it confirms or its blockhash expires",
+        value_parser = clap::value_parser!(u64).range(1..),
+        global = true
+    )]
+    resubmit_interval_ms: Option<u64>,
+
+    #[arg(
+        long,
+        help = "After a transaction is confirmed, wait until it is finalized",
+        global = true
+    )]
+    wait_for_finality: bool,
+
+    #[arg(
+        long,
+        help = "When a transaction is rejected with BlockhashNotFound, refresh the blockhash and resubmit right away",
+        global = true
+    )]
+    retry_on_blockhash_not_found: bool,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Exit with an error after N mine or claim submissions fail in a row, instead of retrying forever",
+        value_parser = clap::value_parser!(u64).range(1..),
+        global = true
+    )]
+    max_consecutive_failures: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Maximum number of RPC requests to make concurrently",
+        default_value = "10",
+        value_parser = clap::value_parser!(u64).range(1..),
+        global = true
+    )]
+    max_concurrent_rpcs: u64,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Maximum number of wallets to process in parallel in multi-keypair commands",
+        default_value = "4",
+        value_parser = clap::value_parser!(u64).range(1..),
+        global = true
+    )]
+    max_concurrency: u64,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        help = "Request a larger transaction heap frame. Must be a multiple of 1024 between 32768 and 262144",
+        value_parser = parse_heap_bytes,
+        global = true
+    )]
+    heap_bytes: Option<u32>,
+
+    #[arg(
+        long = "cu-margin",
+        visible_alias = "compute-unit-margin",
+        value_name = "PERCENT",
+        help = "Request PERCENT more compute units than last consumed by the same instruction type, instead of the static limits",
+        global = true
+    )]
+    cu_margin: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Exit immediately on Ctrl-C, skipping --claim-on-exit and the session summary",
+        global = true
+    )]
+    fast_exit: bool,
+
+    #[arg(
+        long,
+        help = "Print the method, response size and latency of every RPC call to stderr",
+        global = true
+    )]
+    log_rpc_calls: bool,
+
+    #[arg(
+        long,
+        value_name = "MILLISECONDS",
+        help = "Give up connecting to the RPC after MILLISECONDS. Requests on an open connection keep their own, longer timeout",
+        default_value = "5000",
+        global = true
+    )]
+    connect_timeout_ms: u64,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Write mining metrics to this InfluxDB endpoint every --report-interval-secs",
+        global = true
+    )]
+    report_to_influxdb: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "The InfluxDB database to write mining metrics to",
+        default_value = "ore",
+        global = true
+    )]
+    influxdb_database: String,
+
+    #[arg(
+        long,
+        value_name = "PUBKEY,...",
+        help = "Comma-separated addresses of the 8 busses, for ore deployments other than the official one",
+        value_delimiter = ',',
+        global = true
+    )]
+    bus_address_override: Option<Vec<Pubkey>>,
+
+    #[arg(
+        long,
+        value_name = "URL,...",
+        help = "Comma-separated extra RPC endpoints that --broadcast-all also sends transactions to",
+        value_delimiter = ',',
+        global = true
+    )]
+    broadcast_rpc: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Send each transaction to --rpc and every --broadcast-rpc at once, using the first to accept it",
+        requires = "broadcast_rpc",
+        conflicts_with = "compress_txs",
+        global = true
+    )]
+    broadcast_all: bool,
+
+    #[arg(
+        long,
+        value_name = "PUBKEY",
+        help = "Address of the treasury, for ore deployments other than the official one",
+        global = true
+    )]
+    treasury_address_override: Option<Pubkey>,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    #[command(about = "Fetch the Ore balance of an account")]
+    Balance(BalanceArgs),
+
+    #[command(about = "Print the effective configuration and where each setting came from")]
+    Config(ConfigArgs),
+
+    #[command(about = "Wait until the cluster reaches a given slot")]
+    BlockUntilSlot(BlockUntilSlotArgs),
+
+    #[command(about = "Fetch the distributable rewards of the busses")]
+    Busses(BussesArgs),
+
+    #[command(about = "Show the time since the last epoch reset and the countdown to the next")]
+    Epoch(EpochArgs),
+
+    #[command(about = "List the mining and claim transactions of an account")]
+    History(HistoryArgs),
+
+    #[command(about = "Mine Ore using local compute")]
+    Mine(MineArgs),
+
+    #[command(about = "Claim available mining rewards")]
+    Claim(ClaimArgs),
+
+    #[command(about = "Resubmit a transaction written with --sign-only")]
+    Recover(RecoverArgs),
+
+    #[command(about = "Fetch your balance of unclaimed mining rewards")]
+    Rewards(RewardsArgs),
+
+    #[command(
+        about = "Print the SOL, ORE and claimable balances of any address, without a keypair"
+    )]
+    Observe(ObserveArgs),
+
+    #[command(
+        about = "Print the addresses of your proof, token account and the ore program accounts"
+    )]
+    ShowAccounts(ShowAccountsArgs),
+
+    #[command(about = "Export all ore program accounts to a JSON file")]
+    Snapshot(SnapshotArgs),
+
+    #[command(about = "Write the ORE balance and claimable rewards to a timestamped JSON file")]
+    SnapshotBalance(SnapshotBalanceArgs),
+
+    #[command(about = "Fetch the treasury account and balance")]
+    Treasury(TreasuryArgs),
+
+    #[command(about = "Check whether a signature is a valid ore mining transaction")]
+    Verify(VerifyArgs),
+
+    #[command(about = "Wrap SOL into your wSOL token account")]
+    WrapSol(WrapSolArgs),
+
+    #[command(about = "Close your wSOL token account, unwrapping its balance into SOL")]
+    UnwrapSol,
+
+    #[cfg(feature = "admin")]
+    #[command(about = "Initialize the program")]
+    Initialize,
+
+    #[cfg(feature = "admin")]
+    #[command(about = "Update the program admin authority")]
+    UpdateAdmin(UpdateAdminArgs),
+
+    #[cfg(feature = "admin")]
+    #[command(about = "Update the mining difficulty")]
+    UpdateDifficulty,
+}
+
+#[derive(Parser, Debug)]
+struct BalanceArgs {
+    #[arg(
+        // long,
+        value_name = "ADDRESS",
+        help = "The address of the account to fetch the balance of"
+    )]
+    pub address: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct BlockUntilSlotArgs {
+    #[arg(
+        long,
+        value_name = "SLOT",
+        help = "The slot to wait for before exiting"
+    )]
+    pub slot: u64,
+}
+
+#[derive(Parser, Debug)]
+pub struct BussesArgs {
+    #[arg(long, help = "Also print the raw bus account data")]
+    pub raw_account: bool,
+}
+
+#[derive(Parser, Debug)]
+struct ConfigArgs {
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        help = "The output format",
+        default_value = "text"
+    )]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+struct EpochArgs {
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        help = "The output format",
+        default_value = "text"
+    )]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+pub struct HistoryArgs {
+    #[arg(
+        value_name = "ADDRESS",
+        help = "The address of the account to fetch the history of"
+    )]
+    pub address: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "COUNT",
+        help = "The maximum number of transactions to fetch",
+        default_value = "10"
+    )]
+    pub limit: usize,
+
+    #[arg(
+        long,
+        value_name = "EPOCH",
+        help = "Only list transactions from this Solana epoch, or \"current\""
+    )]
+    pub target_epoch: Option<history::TargetEpoch>,
+
+    #[arg(
+        long,
+        value_name = "SLOT",
+        help = "Only list transactions from this slot onwards"
+    )]
+    pub from_slot: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "SLOT",
+        help = "Only list transactions up to and including this slot"
+    )]
+    pub until_slot: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "DATE",
+        help = "Only list transactions since this date (YYYY-MM-DD, UTC) or RFC 3339 time",
+        value_parser = utils::parse_timestamp
+    )]
+    pub since: Option<i64>,
+
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        help = "The output format. json prints one object per line",
+        default_value = "text"
+    )]
+    pub format: ReportFormat,
+}
+
+#[derive(Parser, Debug)]
+pub struct RecoverArgs {
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "The file containing the base64 transaction to resubmit"
+    )]
+    pub tx_file: String,
+
+    #[arg(
+        long,
+        help = "Re-sign the transaction with a recent blockhash before resubmitting it"
+    )]
+    pub fresh_blockhash: bool,
+}
+
+#[derive(Parser, Debug)]
+struct RewardsArgs {
+    #[arg(
+        // long,
+        value_name = "ADDRESS",
+        help = "The address of the account to fetch the rewards balance of"
+    )]
+    pub address: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "COMMITMENT",
+        help = "Commitment level to read the proof account at (processed, confirmed, finalized)",
+        default_value = "confirmed"
+    )]
+    pub proof_commitment: CommitmentConfig,
+
+    #[arg(long, help = "Also print the raw proof account data")]
+    pub raw_account: bool,
+}
+
+impl Commands {
+    /// Returns whether the command signs transactions or otherwise needs the keypair.
+    /// Read-only commands run without one, defaulting to its pubkey only when it is loaded.
+    fn requires_keypair(&self) -> bool {
+        !matches!(
+            self,
+            Commands::Balance(_)
+                | Commands::Config(_)
+                | Commands::BlockUntilSlot(_)
+                | Commands::Busses(_)
+                | Commands::Epoch(_)
+                | Commands::History(_)
+                | Commands::Rewards(_)
+                | Commands::Observe(_)
+                | Commands::Snapshot(_)
+                | Commands::SnapshotBalance(_)
+                | Commands::Treasury(_)
+                | Commands::Verify(_)
+        )
+    }
+
+    /// Returns whether the command submits transactions, so startup checks that only
+    /// matter for landing them (clock skew, program compatibility) can be skipped otherwise.
+    fn submits_transactions(&self) -> bool {
+        self.requires_keypair() && !matches!(self, Commands::ShowAccounts(_))
+    }
+}
+
+#[derive(Parser, Debug)]
+struct ObserveArgs {
+    #[arg(value_name = "ADDRESS", help = "The address to observe")]
+    pub address: Pubkey,
+
+    #[arg(long, help = "Keep refreshing the balances until interrupted")]
+    pub watch: bool,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "How often --watch refreshes the balances",
+        default_value = "10",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub interval_secs: u64,
+}
+
+#[derive(Parser, Debug)]
+struct ShowAccountsArgs {
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        help = "The output format",
+        default_value = "text"
+    )]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+pub struct SnapshotArgs {
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "The file to write the snapshot to",
+        default_value = "ore-snapshot.json"
+    )]
+    pub output: String,
+}
+
+#[derive(Parser, Debug)]
+pub struct SnapshotBalanceArgs {
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "The directory to write snapshot files to",
+        default_value = "."
+    )]
+    pub output_dir: String,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Snapshot every keypair file in this directory instead of the default keypair"
+    )]
+    pub keypairs_dir: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+pub struct TreasuryArgs {
+    #[arg(long, help = "Also print the raw treasury account data")]
+    pub raw_account: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct VerifyArgs {
+    #[arg(
+        long,
+        value_name = "SIGNATURE",
+        help = "The signature of the transaction to verify"
+    )]
+    pub signature: String,
+
+    #[arg(
+        long,
+        value_name = "HASH",
+        help = "The proof's challenge when the transaction was submitted. The current challenge has already advanced past a landed mine"
+    )]
+    pub challenge: String,
+}
+
+#[derive(Parser, Debug)]
+pub struct WrapSolArgs {
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "The amount of SOL to wrap",
+        value_parser = utils::parse_sol_amount
+    )]
+    pub amount: u64,
+}
+
+#[derive(Parser, Debug)]
+pub struct ClaimArgs {
+    #[arg(
+        long,
+        help = "Wait for the proof account's claimable rewards to increase before claiming"
+    )]
+    pub wait_for_update: bool,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "How long to wait for a proof update before using the current on-chain value",
+        default_value = "30"
+    )]
+    pub wait_timeout_secs: u64,
+
+    #[arg(
+        long,
+        value_name = "COMMITMENT",
+        help = "Commitment level to read the claimable rewards at (processed, confirmed, finalized)",
+        default_value = "confirmed"
+    )]
+    pub proof_commitment: CommitmentConfig,
+
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        help = "Claim this percentage of the claimable rewards instead of all of them",
+        value_parser = parse_percent,
+        conflicts_with = "amount"
+    )]
+    pub percent: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "ORE",
+        help = "Claim this many ORE instead of all claimable rewards (e.g. 1.5, 1_000, \"2 ORE\")",
+        value_parser = utils::parse_nonzero_ore_amount
+    )]
+    pub amount: Option<u64>,
 }
 
 #[derive(Parser, Debug)]
-#[command(about, version)]
-struct Args {
+pub struct MineArgs {
     #[arg(
         long,
-        value_name = "NETWORK_URL",
-        help = "Network address of your RPC provider",
-        global = true
+        short,
+        value_name = "THREAD_COUNT",
+        help = "The number of threads to dedicate to mining",
+        default_value = "1",
+        value_parser = clap::value_parser!(u64).range(1..)
     )]
-    rpc: Option<String>,
+    pub threads: u64,
 
-    #[clap(
-        global = true,
-        short = 'C',
-        long = "config",
-        id = "PATH",
-        help = "Filepath to config file."
+    #[arg(
+        long,
+        value_name = "LAMPORTS",
+        help = "Skip submitting a round if its expected fees exceed this many lamports"
     )]
-    pub config_file: Option<String>,
+    pub max_fee_per_round_lamports: Option<u64>,
 
     #[arg(
         long,
-        value_name = "KEYPAIR_FILEPATH",
-        help = "Filepath to keypair to use",
-        global = true
+        value_name = "LAMPORTS",
+        help = "Skip submitting a round unless its reward exceeds its fees by this many lamports",
+        requires = "ore_price_lamports"
     )]
-    keypair: Option<String>,
+    pub min_profit_lamports: Option<u64>,
 
     #[arg(
         long,
-        value_name = "MICROLAMPORTS",
-        help = "Number of microlamports to pay as priority fee per transaction",
-        default_value = "0",
-        global = true
+        value_name = "LAMPORTS",
+        help = "The value of 1 ORE in lamports, used to estimate round profitability"
     )]
-    priority_fee: u64,
+    pub ore_price_lamports: Option<u64>,
 
-    #[command(subcommand)]
-    command: Commands,
-}
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Print a mining status report every SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub report_interval_secs: Option<u64>,
 
-#[derive(Subcommand, Debug)]
-enum Commands {
-    #[command(about = "Fetch the Ore balance of an account")]
-    Balance(BalanceArgs),
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        help = "The format of periodic mining reports",
+        default_value = "text"
+    )]
+    pub mining_report_format: ReportFormat,
 
-    #[command(about = "Fetch the distributable rewards of the busses")]
-    Busses,
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        help = "The format of found solution logs. json and csv print the hash as hex",
+        default_value = "text"
+    )]
+    pub solution_log_format: ReportFormat,
 
-    #[command(about = "Mine Ore using local compute")]
-    Mine(MineArgs),
+    #[arg(
+        long,
+        value_name = "ORE",
+        help = "Warn in periodic reports when rewards fall below this many ORE per hour",
+        requires = "report_interval_secs"
+    )]
+    pub expected_rewards_per_hour: Option<f64>,
 
-    #[command(about = "Claim available mining rewards")]
-    Claim,
+    #[arg(
+        long,
+        value_name = "FRACTION",
+        help = "Fraction of --expected-rewards-per-hour below which to warn",
+        default_value = "0.8"
+    )]
+    pub reward_alert_threshold: f64,
 
-    #[command(about = "Fetch your balance of unclaimed mining rewards")]
-    Rewards(RewardsArgs),
+    #[arg(
+        long,
+        value_name = "FRACTION",
+        help = "Warn in periodic reports when the fraction of submitted transactions that land falls below this",
+        requires = "report_interval_secs"
+    )]
+    pub min_landing_rate: Option<f64>,
 
-    #[command(about = "Fetch the treasury account and balance")]
-    Treasury,
+    #[arg(
+        long,
+        help = "Pause mining while the system is running on battery power (requires the `battery` feature)"
+    )]
+    pub pause_on_battery: bool,
 
-    #[cfg(feature = "admin")]
-    #[command(about = "Initialize the program")]
-    Initialize,
+    #[arg(
+        long,
+        value_name = "BITS",
+        help = "Debug: keep searching past solutions with fewer than BITS leading zero bits",
+        value_parser = clap::value_parser!(u32).range(0..=256)
+    )]
+    pub reject_small_hashes: Option<u32>,
 
-    #[cfg(feature = "admin")]
-    #[command(about = "Update the program admin authority")]
-    UpdateAdmin(UpdateAdminArgs),
+    #[arg(
+        long,
+        help = "Submit on a random bus without checking its rewards first. Saves an RPC round trip, \
+                but the transaction fee is wasted if the bus turns out to be empty"
+    )]
+    pub skip_bus_check: bool,
 
-    #[cfg(feature = "admin")]
-    #[command(about = "Update the mining difficulty")]
-    UpdateDifficulty,
-}
+    #[arg(
+        long,
+        value_name = "MIN_ORE",
+        help = "Only submit on busses with at least MIN_ORE rewards, falling back to the richest bus",
+        value_parser = utils::parse_ore_amount,
+        conflicts_with = "skip_bus_check"
+    )]
+    pub filter_bus_by_reward: Option<u64>,
 
-#[derive(Parser, Debug)]
-struct BalanceArgs {
     #[arg(
-        // long,
-        value_name = "ADDRESS",
-        help = "The address of the account to fetch the balance of"
+        long,
+        help = "Mine and submit a single round, then exit. Exits nonzero if the round did not land"
     )]
-    pub address: Option<String>,
-}
+    pub once: bool,
 
-#[derive(Parser, Debug)]
-struct RewardsArgs {
     #[arg(
-        // long,
-        value_name = "ADDRESS",
-        help = "The address of the account to fetch the rewards balance of"
+        long,
+        value_name = "SECONDS",
+        help = "Stop mining after SECONDS, once the round in progress completes"
     )]
-    pub address: Option<String>,
-}
+    pub max_session_duration_secs: Option<u64>,
 
-#[derive(Parser, Debug)]
-struct MineArgs {
     #[arg(
         long,
-        short,
-        value_name = "THREAD_COUNT",
-        help = "The number of threads to dedicate to mining",
-        default_value = "1"
+        help = "Claim all rewards when mining stops at --max-session-duration-secs or on Ctrl-C"
+    )]
+    pub claim_on_exit: bool,
+
+    #[arg(long, help = "Claim any pending rewards before mining starts")]
+    pub claim_on_start: bool,
+
+    #[arg(
+        long,
+        help = "Pin each mining thread to a distinct CPU core. Can hurt hash rate on shared machines"
+    )]
+    pub pin_threads: bool,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Warn when the proof was last updated more than SECONDS ago, and wait briefly when it was updated under a second ago"
+    )]
+    pub max_proof_age_secs: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Run mining threads at this scheduling niceness (-20 to 19, higher is lower priority). Unix only. On Linux only the mining threads are affected, elsewhere the whole process",
+        value_parser = clap::value_parser!(i32).range(-20..=19),
+        allow_negative_numbers = true
+    )]
+    pub nice: Option<i32>,
+
+    #[arg(
+        long,
+        help = "Only search nonces in the u32 range. Faster to exhaust on slow hardware, meant for devnet/testnet difficulty"
+    )]
+    pub max_nonce_u32: bool,
+
+    #[arg(
+        long,
+        help = "Fetch all bus states at startup and after each submission, so submissions don't wait on them"
+    )]
+    pub pre_load_buses: bool,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Restart the mining threads if no hashes are computed for SECONDS, e.g. after a worker panics or deadlocks",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub watchdog_timeout: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Print the SOL, ORE and claimable balances every SECONDS while mining. 0 disables it",
+        default_value = "0"
+    )]
+    pub balance_refresh_interval_secs: u64,
+
+    #[arg(
+        long,
+        help = "Exit with code 4 if the ORE program is upgraded while mining, so a supervisor can restart with an updated binary"
+    )]
+    pub halt_on_program_upgrade: bool,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "How often --halt-on-program-upgrade checks the ORE program",
+        default_value = "300",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub program_check_interval_secs: u64,
+
+    #[arg(
+        long,
+        value_name = "ALGO",
+        help = "The hash function to search nonces with. Only keccak256 is accepted by the deployed program, the others are for devnet experiments",
+        default_value = "keccak256"
+    )]
+    pub hash_algorithm: hasher::HashAlgorithm,
+
+    #[arg(
+        long,
+        value_name = "URL|fd:N",
+        help = "Also send each solution (proof, challenge, hash, nonce) as JSON to an HTTP(S) endpoint, or as a JSON line to an inherited file descriptor"
+    )]
+    pub solution_sink: Option<solution_sink::SolutionSinkTarget>,
+
+    #[arg(
+        long,
+        help = "Leave submission to the external submitter behind --solution-sink instead of submitting solutions",
+        requires = "solution_sink"
     )]
-    threads: u64,
+    pub sink_only: bool,
 }
 
 #[cfg(feature = "admin")]
@@ -149,49 +1127,161 @@ struct UpdateAdminArgs {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    match (&args.command, &args.submit_signed_tx) {
+        (None, None) => Args::command()
+            .error(ErrorKind::MissingSubcommand, "a subcommand is required")
+            .exit(),
+        (Some(_), Some(_)) => Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--submit-signed-tx cannot be used with a subcommand",
+            )
+            .exit(),
+        _ => {}
+    }
 
     // Load the config file from custom path, the default path, or use default config values
-    let cli_config = if let Some(config_file) = &args.config_file {
-        solana_cli_config::Config::load(config_file).unwrap_or_else(|_| {
+    let config_file = args
+        .config_file
+        .clone()
+        .or(solana_cli_config::CONFIG_FILE.clone());
+    let (cli_config, cli_config_source) = if let Some(config_file) = &args.config_file {
+        let cli_config = solana_cli_config::Config::load(config_file).unwrap_or_else(|_| {
             eprintln!("error: Could not find config file `{}`", config_file);
             std::process::exit(1);
-        })
+        });
+        (cli_config, format!("config file {}", config_file))
     } else if let Some(config_file) = &*solana_cli_config::CONFIG_FILE {
-        solana_cli_config::Config::load(config_file).unwrap_or_default()
+        match solana_cli_config::Config::load(config_file) {
+            Ok(cli_config) => (cli_config, format!("config file {}", config_file)),
+            Err(_) => (solana_cli_config::Config::default(), "default".into()),
+        }
     } else {
-        solana_cli_config::Config::default()
+        (solana_cli_config::Config::default(), "default".into())
     };
 
     // Initialize miner.
-    let cluster = args.rpc.unwrap_or(cli_config.json_rpc_url);
-    let default_keypair = args.keypair.unwrap_or(cli_config.keypair_path);
+    let cluster = args.rpc.clone().unwrap_or(cli_config.json_rpc_url);
+    let default_keypair = args.keypair.clone().unwrap_or(cli_config.keypair_path);
+
+    // Print the effective configuration without connecting to the RPC
+    if let Some(Commands::Config(config_args)) = &args.command {
+        config::dump_config(
+            &matches,
+            &cli_config_source,
+            &cluster,
+            &default_keypair,
+            args.keypair_env.as_deref(),
+            config_args.format,
+        );
+        return Ok(());
+    }
+
+    // Observe an address without loading a keypair
+    if let Some(Commands::Observe(observe_args)) = args.command {
+        let rpc_client = rpc_log::new_rpc_client(
+            cluster,
+            CommitmentConfig::confirmed(),
+            args.log_rpc_calls,
+            args.connect_timeout_ms,
+            Arc::new(Semaphore::new(args.max_concurrent_rpcs as usize)),
+        );
+        observe::observe(&rpc_client, observe_args).await;
+        return Ok(());
+    }
+
+    if args.keypair_watch && default_keypair.eq("-") {
+        anyhow::bail!("--keypair-watch cannot be used with a keypair read from stdin");
+    }
+    let require_keypair = args
+        .command
+        .as_ref()
+        .is_some_and(Commands::requires_keypair);
+    let miner = Miner::new(cluster.clone(), &default_keypair, &args, require_keypair).await?;
+    if args
+        .command
+        .as_ref()
+        .is_some_and(Commands::submits_transactions)
+    {
+        warn_if_clock_skewed(&miner.rpc_client).await;
+        warn_if_program_incompatible(&miner.rpc_client, &miner.addresses.treasury).await;
+        miner.warn_if_fee_bump_unreachable();
+    }
+    if let Some(pubkey) = miner.pubkey() {
+        utils::load_keypair_aliases(config_file.as_deref(), pubkey, args.keypair_alias.clone());
+    }
+    if args.rpc_keepalive_secs.gt(&0) {
+        tokio::spawn(keep_rpc_alive(
+            miner.rpc_client.clone(),
+            args.rpc_keepalive_secs,
+        ));
+    }
+    if let (true, Some(keypair)) = (args.keypair_watch, miner.keypair.as_ref()) {
+        tokio::spawn(watch_keypair(default_keypair.clone(), keypair.clone()));
+    }
 
-    let miner = Miner::new(cluster.clone(), args.priority_fee, &default_keypair).await?;
-    tokio::spawn(poll_latest_blockhash(
-        miner.clone_rpc_client(),
-        miner.latest_blockhash.clone(),
-    ));
+    // Submit a transaction signed earlier with --sign-only
+    let Some(command) = args.command else {
+        if let Some(path) = args.submit_signed_tx.as_ref() {
+            miner.submit_signed_tx(path).await;
+        }
+        return Ok(());
+    };
 
     // Execute user command.
-    match args.command {
+    match command {
         Commands::Balance(args) => {
             miner.balance(args.address).await;
         }
-        Commands::Busses => {
-            miner.busses().await;
+        Commands::Config(_) | Commands::Observe(_) => unreachable!(),
+        Commands::BlockUntilSlot(args) => {
+            miner.block_until_slot(args.slot).await;
+        }
+        Commands::Busses(args) => {
+            miner.busses(args).await;
+        }
+        Commands::Epoch(args) => {
+            miner.epoch(args.format).await;
+        }
+        Commands::History(args) => {
+            miner.history(args).await;
+        }
+        Commands::Recover(args) => {
+            miner.recover(args).await;
         }
         Commands::Rewards(args) => {
-            miner.rewards(args.address).await;
+            miner
+                .rewards(args.address, args.proof_commitment, args.raw_account)
+                .await;
+        }
+        Commands::ShowAccounts(args) => {
+            miner.show_accounts(args.format);
+        }
+        Commands::Snapshot(args) => {
+            miner.snapshot(args).await;
         }
-        Commands::Treasury => {
-            miner.treasury().await;
+        Commands::SnapshotBalance(args) => {
+            miner.snapshot_balance(args).await;
+        }
+        Commands::Treasury(args) => {
+            miner.treasury(args).await;
         }
         Commands::Mine(args) => {
-            miner.mine(args.threads).await;
+            miner.mine(args).await?;
+        }
+        Commands::Claim(args) => {
+            miner.claim(args).await;
+        }
+        Commands::Verify(args) => {
+            miner.verify(args).await;
+        }
+        Commands::WrapSol(args) => {
+            miner.wrap_sol(args).await;
         }
-        Commands::Claim => {
-            miner.claim().await;
+        Commands::UnwrapSol => {
+            miner.unwrap_sol().await;
         }
         #[cfg(feature = "admin")]
         Commands::Initialize => {
@@ -211,47 +1301,391 @@ async fn main() -> Result<()> {
 }
 
 impl Miner {
-    pub async fn new(rpc_url: String, priority_fee: u64, keypair_filepath: &str) -> Result<Self> {
-        let keypair = read_keypair_file(keypair_filepath).map_err(|e| anyhow::anyhow!("{}", e))?;
-        let rpc_client =
-            RpcClient::new_with_commitment(rpc_url.clone(), CommitmentConfig::confirmed());
-
-        let blockhash = rpc_client
-            .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
-            .await?;
-
-        let latest_blockhash = Arc::new(Mutex::new(blockhash));
+    /// Connects to the RPC and loads the keypair. Without `require_keypair`, a keypair
+    /// that fails to load is skipped so read-only commands still run.
+    pub async fn new(
+        rpc_url: String,
+        keypair_filepath: &str,
+        args: &Args,
+        require_keypair: bool,
+    ) -> Result<Self> {
+        let keypair = match load_keypair(keypair_filepath, args) {
+            Ok(keypair) => Some(keypair),
+            Err(err) if !require_keypair => {
+                if args.keypair.is_some() || args.keypair_env.is_some() {
+                    eprintln!("warning: Continuing without a keypair: {}", err);
+                }
+                None
+            }
+            Err(err) => return Err(err),
+        };
+        let mut addresses = ProgramAddresses::default();
+        if let Some(busses) = args.bus_address_override.as_ref() {
+            addresses.busses = busses.as_slice().try_into().map_err(|_| {
+                anyhow::anyhow!(
+                    "--bus-address-override takes exactly {} addresses, got {}",
+                    BUS_COUNT,
+                    busses.len()
+                )
+            })?;
+        }
+        if let Some(treasury) = args.treasury_address_override {
+            addresses.treasury = treasury;
+        }
+        let rpc_permits = Arc::new(Semaphore::new(args.max_concurrent_rpcs as usize));
+        let rpc_client = rpc_log::new_rpc_client(
+            rpc_url.clone(),
+            CommitmentConfig::confirmed(),
+            args.log_rpc_calls,
+            args.connect_timeout_ms,
+            rpc_permits.clone(),
+        );
+        let broadcast_clients = if args.broadcast_all {
+            args.broadcast_rpc
+                .iter()
+                .map(|url| {
+                    Arc::new(rpc_log::new_rpc_client(
+                        url.clone(),
+                        CommitmentConfig::confirmed(),
+                        args.log_rpc_calls,
+                        args.connect_timeout_ms,
+                        rpc_permits.clone(),
+                    ))
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        let tag = args.tag.clone().unwrap_or_else(utils::hostname);
 
         Ok(Self {
-            keypair,
-            priority_fee,
+            keypair: keypair.map(|keypair| Arc::new(RwLock::new(Arc::new(keypair)))),
+            priority_fee: args.priority_fee,
+            priority_fee_decay: args.priority_fee_decay,
+            priority_fee_increase_on_fail: args.priority_fee_increase_on_fail,
+            max_priority_fee_cap: args.max_priority_fee_cap,
+            auto_priority_fee_bump_secs: args.auto_priority_fee_bump_secs,
+            auto_priority_fee_bump_amount: args.auto_priority_fee_bump_amount,
+            current_priority_fee: Mutex::new((args.priority_fee, Instant::now())),
+            fee_percentile: args.fee_percentile,
+            fee_unit: args.fee_unit,
+            min_priority_fee: args.min_priority_fee,
+            percentile_fee_cache: Mutex::new(None),
+            fee_oracle: args.fee_oracle.clone(),
+            priority_fee_account: args.use_priority_fee_account,
+            oracle_fee_cache: Mutex::new(None),
+            compress_txs: AtomicBool::new(args.compress_txs),
+            simulate_before_submit: args.simulate_before_submit,
+            sign_only: args.sign_only,
+            unsigned_tx_output: args.unsigned_tx_output.clone(),
+            compression_stats: Mutex::new((0, 0, 0)),
+            session_stats: Arc::new(SessionStats::new()),
+            events: Arc::new(EventSink::new(args.events_file.as_deref(), tag.clone())?),
+            notify_webhook: args.notify_webhook.clone(),
+            tag,
+            log_prefix: args
+                .tag
+                .as_ref()
+                .map(|tag| format!("[{}] ", tag))
+                .unwrap_or_default(),
+            rpc_send_retries: args.rpc_send_retries,
+            heap_bytes: args.heap_bytes,
+            cu_margin: args.cu_margin,
+            observed_cus: Mutex::new(HashMap::new()),
+            fast_exit: args.fast_exit,
+            confirm_poll_interval_ms: args.confirm_poll_interval_ms,
+            resubmit_interval_ms: args.resubmit_interval_ms,
+            wait_for_finality: args.wait_for_finality,
+            retry_on_blockhash_not_found: args.retry_on_blockhash_not_found,
+            max_consecutive_failures: args.max_consecutive_failures,
+            consecutive_failures: AtomicU64::new(0),
+            wallet_permits: Semaphore::new(args.max_concurrency as usize),
+            shutdown: Arc::new(AtomicBool::new(false)),
+            influxdb: args.report_to_influxdb.clone().map(|url| InfluxDbConfig {
+                url,
+                database: args.influxdb_database.clone(),
+            }),
+            addresses,
             rpc_url,
-            rpc_client,
-            latest_blockhash,
+            rpc_client: Arc::new(rpc_client),
+            broadcast_clients,
+            log_rpc_calls: args.log_rpc_calls,
+            connect_timeout_ms: args.connect_timeout_ms,
+            rpc_permits,
+            http_client: utils::new_http_client(args.connect_timeout_ms),
+            latest_blockhash: Arc::new(Mutex::new((Hash::default(), 0))),
+            blockhash_poller: OnceCell::new(),
+            stall_detection_secs: args.stall_detection_secs,
         })
     }
 
     pub fn clone_rpc_client(&self) -> RpcClient {
-        RpcClient::new_with_commitment(self.rpc_url.clone(), CommitmentConfig::confirmed())
+        rpc_log::new_rpc_client(
+            self.rpc_url.clone(),
+            CommitmentConfig::confirmed(),
+            self.log_rpc_calls,
+            self.connect_timeout_ms,
+            self.rpc_permits.clone(),
+        )
+    }
+
+    /// Counts a mine or claim submission that failed after retries, exiting once
+    /// `--max-consecutive-failures` have failed in a row.
+    pub fn on_submission_failed(&self, err: &str) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        let Some(max_failures) = self.max_consecutive_failures else {
+            return;
+        };
+        if failures.ge(&max_failures) {
+            eprintln!(
+                "error: {} submissions failed in a row (--max-consecutive-failures {}), aborting. Last error: {}",
+                failures, max_failures, err
+            );
+            eprintln!("Check the signer's SOL balance, the RPC's health and whether the ORE program was upgraded");
+            std::process::exit(EXIT_CONSECUTIVE_FAILURES);
+        }
+    }
+
+    /// Resets the consecutive failure count after a submission lands.
+    pub fn on_submission_succeeded(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns the loaded keypair. Only commands that require a keypair may call this.
+    pub fn signer(&self) -> Arc<Keypair> {
+        let lock = self
+            .keypair
+            .as_ref()
+            .expect("command requires a keypair")
+            .read()
+            .unwrap();
+        lock.clone()
+    }
+
+    /// Returns the pubkey of the loaded keypair, if any.
+    pub fn pubkey(&self) -> Option<Pubkey> {
+        self.keypair
+            .as_ref()
+            .map(|keypair| keypair.read().unwrap().pubkey())
     }
 
-    pub fn signer(&self) -> &Keypair {
-        &self.keypair
+    /// Returns the pubkey read-only commands default to when no address is given,
+    /// printing a hint when no keypair was loaded.
+    pub fn default_address(&self) -> Option<Pubkey> {
+        let pubkey = self.pubkey();
+        if pubkey.is_none() {
+            println!("No keypair loaded. Pass an address, or a keypair with --keypair");
+        }
+        pubkey
     }
 
-    pub fn get_latest_blockhash(&self) -> (Hash, u64) {
+    /// Returns the latest blockhash kept fresh by the poller. The first call fetches it
+    /// and starts the poller, so commands that never submit don't wait on a blockhash.
+    pub async fn get_latest_blockhash(&self) -> ClientResult<(Hash, u64)> {
+        self.blockhash_poller
+            .get_or_try_init(|| async {
+                let blockhash = self
+                    .rpc_client
+                    .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
+                    .await?;
+                *self.latest_blockhash.lock().unwrap() = blockhash;
+                let rpc_url = self.rpc_url.clone();
+                let log_rpc_calls = self.log_rpc_calls;
+                let connect_timeout_ms = self.connect_timeout_ms;
+                let rpc_permits = self.rpc_permits.clone();
+                tokio::spawn(poll_latest_blockhash(
+                    move || {
+                        rpc_log::new_rpc_client(
+                            rpc_url.clone(),
+                            CommitmentConfig::confirmed(),
+                            log_rpc_calls,
+                            connect_timeout_ms,
+                            rpc_permits.clone(),
+                        )
+                    },
+                    self.latest_blockhash.clone(),
+                    self.stall_detection_secs,
+                ));
+                ClientResult::Ok(())
+            })
+            .await?;
         let lock = self.latest_blockhash.lock().unwrap();
-        *lock
+        Ok(*lock)
+    }
+
+    /// Fetches a new blockhash right away instead of waiting for the poller.
+    pub async fn refresh_latest_blockhash(&self) -> ClientResult<(Hash, u64)> {
+        self.get_latest_blockhash().await?;
+        let blockhash = self
+            .rpc_client
+            .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
+            .await?;
+        *self.latest_blockhash.lock().unwrap() = blockhash;
+        Ok(blockhash)
+    }
+}
+
+fn parse_percent(s: &str) -> Result<f64, String> {
+    let percent: f64 = s.parse().map_err(|e| format!("{}", e))?;
+    if !(percent > 0.0 && percent <= 100.0) {
+        return Err("must be greater than 0 and at most 100".into());
+    }
+    Ok(percent)
+}
+
+fn parse_heap_bytes(s: &str) -> Result<u32, String> {
+    let bytes: u32 = s.parse().map_err(|e| format!("{}", e))?;
+    if !(MIN_HEAP_FRAME_BYTES..=MAX_HEAP_FRAME_BYTES).contains(&bytes)
+        || !bytes.is_multiple_of(1024)
+    {
+        return Err(format!(
+            "must be a multiple of 1024 between {} and {}",
+            MIN_HEAP_FRAME_BYTES, MAX_HEAP_FRAME_BYTES
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Reads the keypair from `--keypair-env`, stdin (`--keypair -`) or the keypair file.
+fn load_keypair(keypair_filepath: &str, args: &Args) -> Result<Keypair> {
+    match args.keypair_env.as_ref() {
+        Some(var) => read_keypair_env(var),
+        None if keypair_filepath.eq("-") => read_keypair_stdin(),
+        None => {
+            let keypair =
+                read_keypair_file(keypair_filepath).map_err(|e| anyhow::anyhow!("{}", e))?;
+            warn_if_keypair_permissions_too_open(keypair_filepath);
+            Ok(keypair)
+        }
+    }
+}
+
+/// Reads a keypair from an environment variable holding either a JSON array of 64 bytes
+/// or a base58-encoded private key.
+fn read_keypair_env(var: &str) -> Result<Keypair> {
+    let value = std::env::var(var)
+        .map_err(|e| anyhow::anyhow!("Failed to read keypair from ${}: {}", var, e))?;
+    parse_keypair(&value, &format!("${}", var))
+}
+
+fn read_keypair_stdin() -> Result<Keypair> {
+    let mut value = String::new();
+    std::io::stdin()
+        .read_to_string(&mut value)
+        .map_err(|e| anyhow::anyhow!("Failed to read keypair from stdin: {}", e))?;
+    parse_keypair(&value, "stdin")
+}
+
+/// Parses a keypair given as a JSON byte array or a base58 private key.
+fn parse_keypair(value: &str, source: &str) -> Result<Keypair> {
+    let value = value.trim();
+    if value.is_empty() {
+        anyhow::bail!("No keypair provided on {}", source);
+    }
+    let bytes = if value.starts_with('[') {
+        serde_json::from_str::<Vec<u8>>(value)
+            .map_err(|e| anyhow::anyhow!("Invalid keypair JSON in {}: {}", source, e))?
+    } else {
+        bs58::decode(value)
+            .into_vec()
+            .map_err(|e| anyhow::anyhow!("Invalid base58 keypair in {}: {}", source, e))?
+    };
+    Keypair::from_bytes(&bytes).map_err(|e| anyhow::anyhow!("Invalid keypair in {}: {}", source, e))
+}
+
+async fn warn_if_clock_skewed(rpc_client: &RpcClient) {
+    let Ok(slot) = rpc_client.get_slot().await else {
+        return;
+    };
+    let Ok(block_time) = rpc_client.get_block_time(slot).await else {
+        return;
+    };
+    let local_time = chrono::Utc::now().timestamp();
+    let skew = local_time - block_time;
+    if skew.abs() > MAX_CLOCK_SKEW_SECS {
+        eprintln!(
+            "warning: Local clock is skewed from the cluster by {}s (local {}, cluster {}). This can cause blockhash and timing issues",
+            skew, local_time, block_time
+        );
+    }
+}
+
+/// Warns if the ore program or its treasury don't look like the version this CLI was built against,
+/// since hardcoded CU limits and account layouts would then be wrong.
+async fn warn_if_program_incompatible(rpc_client: &RpcClient, treasury: &Pubkey) {
+    let Ok(program) = rpc_client
+        .get_account_with_commitment(&ore::ID, CommitmentConfig::confirmed())
+        .await
+    else {
+        return;
+    };
+    match program.value {
+        Some(program) if !program.executable => {
+            eprintln!(
+                "warning: Account {} is not an executable program. Is --rpc pointing at the right cluster?",
+                ore::ID
+            );
+            return;
+        }
+        Some(_) => {}
+        None => {
+            eprintln!(
+                "warning: Ore program {} was not found on this cluster",
+                ore::ID
+            );
+            return;
+        }
+    }
+    if let Ok(data) = rpc_client.get_account_data(treasury).await {
+        if Treasury::try_from_bytes(&data).is_err() {
+            eprintln!(
+                "warning: The treasury account does not match the layout this CLI was built for (ore-program {}). The program may have been upgraded",
+                ORE_PROGRAM_VERSION
+            );
+        }
+    }
+}
+
+#[cfg(unix)]
+fn warn_if_keypair_permissions_too_open(keypair_filepath: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Ok(metadata) = std::fs::metadata(keypair_filepath) {
+        let mode = metadata.permissions().mode();
+        if mode & 0o077 != 0 {
+            eprintln!(
+                "warning: Keypair file `{}` is accessible by other users (mode {:o}). Consider running `chmod 600 {}`",
+                keypair_filepath,
+                mode & 0o777,
+                keypair_filepath
+            );
+        }
     }
 }
 
+#[cfg(not(unix))]
+fn warn_if_keypair_permissions_too_open(_keypair_filepath: &str) {}
+
 pub async fn poll_latest_blockhash(
-    rpc_client: RpcClient,
+    new_rpc_client: impl Fn() -> RpcClient,
     latest_blockhash: Arc<Mutex<(Hash, u64)>>,
+    stall_detection_secs: u64,
 ) -> ! {
+    let mut rpc_client = new_rpc_client();
+    let mut last_changed = Instant::now();
     loop {
         tokio::time::sleep(std::time::Duration::from_secs(10)).await;
 
+        // Reconnect and refetch if the blockhash has not changed in a while
+        if last_changed.elapsed().as_secs().ge(&stall_detection_secs) {
+            println!(
+                "No blockhash update in {}s, reconnecting to {}",
+                last_changed.elapsed().as_secs(),
+                rpc_client.url()
+            );
+            rpc_client = new_rpc_client();
+            last_changed = Instant::now();
+        }
+
         let blockhash = match rpc_client
             .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
             .await
@@ -264,6 +1698,62 @@ pub async fn poll_latest_blockhash(
         };
 
         let mut lock = latest_blockhash.lock().unwrap();
+        if lock.0.ne(&blockhash.0) {
+            last_changed = Instant::now();
+        }
         *lock = blockhash;
     }
 }
+
+/// Pings the RPC every `interval_secs` so idle connections aren't dropped by NATs or firewalls.
+pub async fn keep_rpc_alive(rpc_client: Arc<RpcClient>, interval_secs: u64) -> ! {
+    let mut failures = 0;
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+        match rpc_client.get_health().await {
+            Ok(()) => failures = 0,
+            Err(err) => {
+                failures += 1;
+                println!("WARNING: RPC health check failed: {:?}", err);
+                if failures.eq(&KEEPALIVE_MAX_FAILURES) {
+                    println!(
+                        "WARNING: RPC health check failed {} times in a row, the RPC may be unreachable",
+                        failures
+                    );
+                }
+            }
+        }
+    }
+}
+
+pub async fn watch_keypair(keypair_filepath: String, keypair: Arc<RwLock<Arc<Keypair>>>) -> ! {
+    let modified_at = |path: &str| -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    };
+    let mut last_modified = modified_at(&keypair_filepath);
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+
+        let modified = modified_at(&keypair_filepath);
+        if modified.is_none() || modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        let new_keypair = match read_keypair_file(&keypair_filepath) {
+            Ok(new_keypair) => new_keypair,
+            Err(e) => {
+                println!("Failed to reload keypair: {:?}", e);
+                continue;
+            }
+        };
+
+        let mut lock = keypair.write().unwrap();
+        println!(
+            "Keypair reloaded: {} -> {}",
+            utils::format_pubkey(&lock.pubkey()),
+            utils::format_pubkey(&new_keypair.pubkey())
+        );
+        *lock = Arc::new(new_keypair);
+    }
+}
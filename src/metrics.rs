@@ -0,0 +1,60 @@
+use solana_program::pubkey::Pubkey;
+
+use crate::stats::MiningReport;
+
+/// Where to write mining metrics with `--report-to-influxdb`.
+#[derive(Clone, Debug)]
+pub struct InfluxDbConfig {
+    pub url: String,
+    pub database: String,
+}
+
+/// Serializes a mining report as a single InfluxDB line protocol point, tagged with the miner's pubkey.
+fn to_line_protocol(pubkey: &Pubkey, tag: &str, report: &MiningReport, fees_paid: u64) -> String {
+    let solutions_per_hour = report.rounds as f64 / (report.elapsed_secs.max(1) as f64 / 3600.0);
+    format!(
+        "ore_mining,pubkey={},tag={} hash_rate={},solutions_per_hour={},ore_earned={},fees_paid={}i {}",
+        pubkey,
+        escape_tag_value(tag),
+        report.hash_rate,
+        solutions_per_hour,
+        report.ore_earned,
+        fees_paid,
+        report.timestamp
+    )
+}
+
+/// Escapes the characters line protocol treats specially in tag values.
+fn escape_tag_value(value: &str) -> String {
+    value
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+}
+
+/// Posts the mining report to `<url>/write?db=<database>`.
+pub async fn write_influxdb(
+    http_client: &reqwest::Client,
+    config: &InfluxDbConfig,
+    pubkey: &Pubkey,
+    tag: &str,
+    report: &MiningReport,
+    fees_paid: u64,
+) {
+    let url = format!("{}/write", config.url.trim_end_matches('/'));
+    match http_client
+        .post(url)
+        .query(&[("db", config.database.as_str()), ("precision", "s")])
+        .body(to_line_protocol(pubkey, tag, report, fees_paid))
+        .send()
+        .await
+    {
+        Ok(res) if !res.status().is_success() => {
+            println!("InfluxDB returned status {}", res.status());
+        }
+        Ok(_) => {}
+        Err(err) => {
+            println!("Failed to write metrics to InfluxDB: {:?}", err);
+        }
+    }
+}
@@ -1,73 +1,418 @@
 use crate::{
+    balance::report_balances,
     cu_limits::{CU_LIMIT_MINE, CU_LIMIT_RESET},
-    utils::{get_clock_account, get_proof, get_treasury},
-    Miner,
+    events::Event,
+    hasher::HashAlgorithm,
+    power,
+    solution_sink::{Solution, SolutionSink},
+    stats::{report_session_stats, ReportConfig, ReportFormat, SolutionLog},
+    utils::{format_pubkey, get_clock_account, get_proof, get_treasury, proof_pubkey, resolve_ata},
+    MineArgs, Miner,
 };
-use ore::{self, state::Bus, BUS_ADDRESSES, BUS_COUNT, EPOCH_DURATION};
-use rand::Rng;
+use futures::{stream::FuturesUnordered, StreamExt};
+use ore::{
+    self,
+    state::{Bus, Hash, Proof},
+    BUS_COUNT, EPOCH_DURATION,
+};
+use rand::{seq::SliceRandom, Rng};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
+};
+use solana_program::{bpf_loader_upgradeable, pubkey::Pubkey};
 use solana_sdk::{
     compute_budget::ComputeBudgetInstruction,
     keccak::{hashv, Hash as KeccakHash},
     signature::Signer,
 };
-use std::sync::{atomic::AtomicBool, Arc, Mutex};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Once,
+    },
+    time::{Duration, Instant},
+};
+
+// Upper bound on mining threads, relative to the number of available cores
+const MAX_THREADS_PER_CORE: u64 = 4;
 
 // Odds of being selected to submit a reset tx
 const RESET_ODDS: u64 = 20;
 
+// Delay before re-checking fees against the round budget
+const FEE_BUDGET_DELAY: u64 = 5000;
+
+// Delay before re-checking the busses when none has enough rewards
+const BUS_CHECK_DELAY: u64 = 1000;
+
+// Attempts and delay while waiting for the proof challenge to advance after a submission
+const CHALLENGE_REFRESH_RETRIES: usize = 5;
+const CHALLENGE_REFRESH_DELAY: u64 = 1000;
+
+// Delay between checks of the hash counter by the mining watchdog
+const WATCHDOG_POLL_DELAY: u64 = 1000;
+
+// Submission attempts for a --once round before giving up on it
+const ONCE_SUBMIT_ATTEMPTS: usize = 3;
+
+// Exit code when --halt-on-program-upgrade detects an upgrade
+const EXIT_PROGRAM_UPGRADED: i32 = 4;
+
+// Exit code when the proof account belongs to a different authority than the signer
+const EXIT_PROOF_AUTHORITY_MISMATCH: i32 = 5;
+
+// Genesis hash of mainnet-beta
+const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+
+// How long to wait for an external submitter to land a solution with --sink-only
+const SINK_SUBMISSION_TIMEOUT: u64 = 60;
+
+// Delay before re-reading a proof that was updated less than a second ago
+const PROOF_SETTLE_DELAY: u64 = 1000;
+
+// Warns about a failed --nice once, rather than for every worker on every round
+static NICENESS_WARNING: Once = Once::new();
+
 impl Miner {
-    pub async fn mine(&self, threads: u64) {
+    /// Mines until stopped. Returns an error if mining had to stop early, or with `--once`
+    /// if the round's solution did not land, so scripts can tell from the exit code.
+    pub async fn mine(&self, mut args: MineArgs) -> anyhow::Result<()> {
+        // Guard against typos like --threads 1000 thrashing the machine
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get()) as u64;
+        let max_threads = cores.saturating_mul(MAX_THREADS_PER_CORE);
+        if args.threads.gt(&max_threads) {
+            println!(
+                "{}WARNING: --threads {} is more than {}x the {} available cores, using {} threads",
+                self.log_prefix, args.threads, MAX_THREADS_PER_CORE, cores, max_threads
+            );
+            args.threads = max_threads;
+        }
+
         // Register, if needed.
-        let signer = self.signer();
+        let mut signer = self.signer();
         self.register().await;
         let mut rng = rand::thread_rng();
+        let mut skipped_rounds = 0u64;
+        let mut first_solution = true;
+        let proof = get_proof(&self.rpc_client, signer.pubkey()).await;
+        if !check_proof_authority(&proof, &signer.pubkey()) {
+            std::process::exit(EXIT_PROOF_AUTHORITY_MISMATCH);
+        }
+        let initial_total_rewards = proof.total_rewards;
+        if let Some(interval_secs) = args.report_interval_secs {
+            tokio::spawn(report_session_stats(
+                self.session_stats.clone(),
+                self.events.clone(),
+                ReportConfig {
+                    interval_secs,
+                    format: args.mining_report_format,
+                    expected_rewards_per_hour: args.expected_rewards_per_hour,
+                    reward_alert_threshold: args.reward_alert_threshold,
+                    min_landing_rate: args.min_landing_rate,
+                    notify_webhook: self.notify_webhook.clone(),
+                    http_client: self.http_client.clone(),
+                    influxdb: self.influxdb.clone(),
+                    pubkey: signer.pubkey(),
+                    tag: self.tag.clone(),
+                    log_prefix: self.log_prefix.clone(),
+                },
+            ));
+        }
+        if args.balance_refresh_interval_secs.gt(&0) {
+            tokio::spawn(report_balances(
+                self.rpc_client.clone(),
+                signer.pubkey(),
+                args.balance_refresh_interval_secs,
+            ));
+        }
+        if args.halt_on_program_upgrade {
+            let hash = program_hash(&self.rpc_client).await;
+            if hash.is_none() {
+                println!(
+                    "{}WARNING: Failed to read the ORE program, retrying on the next program check",
+                    self.log_prefix
+                );
+            }
+            tokio::spawn(watch_program_upgrade(
+                self.rpc_client.clone(),
+                hash,
+                args.program_check_interval_secs,
+            ));
+        }
+        if self.influxdb.is_some() && args.report_interval_secs.is_none() {
+            println!("{}WARNING: --report-to-influxdb requires --report-interval-secs, no metrics will be written", self.log_prefix);
+        }
+        if args.pause_on_battery && power::on_battery().is_none() {
+            println!("{}WARNING: Power state detection is unsupported on this build, ignoring --pause-on-battery", self.log_prefix);
+        }
+
+        if let Some(nice) = args.nice {
+            if cfg!(unix) {
+                println!(
+                    "{}Running mining threads at niceness {}",
+                    self.log_prefix, nice
+                );
+            } else {
+                println!(
+                    "{}WARNING: --nice is unsupported on this platform, ignoring it",
+                    self.log_prefix
+                );
+            }
+        }
+
+        if args.hash_algorithm.ne(&HashAlgorithm::Keccak256) {
+            println!(
+                "{}WARNING: ore-program only accepts keccak256 solutions, {:?} solutions will fail on-chain. Use it for devnet experiments only", self.log_prefix, args.hash_algorithm
+            );
+        }
+
+        // Restrict the nonce search to the u32 range, if requested
+        let max_nonce = if args.max_nonce_u32 {
+            if self.is_mainnet().await {
+                println!("{}WARNING: --max-nonce-u32 searches only 2^32 nonces and may find no solution at mainnet difficulty", self.log_prefix);
+            }
+            u32::MAX as u64
+        } else {
+            u64::MAX
+        };
+
+        // Cache the bus states so the first submission doesn't wait on them
+        let mut bus_cache = None;
+        if args.pre_load_buses {
+            if args.skip_bus_check {
+                println!(
+                    "{}WARNING: --pre-load-buses has no effect with --skip-bus-check",
+                    self.log_prefix
+                );
+            } else {
+                bus_cache = Some(self.fetch_busses().await);
+            }
+        }
+
+        if args.pin_threads {
+            for i in 0..args.threads {
+                println!(
+                    "{}Pinning worker {} to core {}",
+                    self.log_prefix,
+                    i,
+                    worker_core(i)
+                );
+            }
+        }
+
+        // Collect rewards left over from an earlier session
+        if args.claim_on_start {
+            let proof = get_proof(&self.rpc_client, signer.pubkey()).await;
+            if proof.claimable_rewards.gt(&0) && !self.claim_rewards(proof.claimable_rewards).await
+            {
+                println!(
+                    "{}WARNING: Failed to claim rewards on start, continuing to mine",
+                    self.log_prefix
+                );
+            }
+        }
+
+        let solution_sink = args
+            .solution_sink
+            .as_ref()
+            .map(|target| SolutionSink::open(target, &self.http_client));
+
+        tokio::spawn(watch_shutdown(self.shutdown.clone(), self.fast_exit));
 
         // Start mining loop
         loop {
+            // End the session on Ctrl-C
+            if self.shutdown.load(Ordering::Relaxed) {
+                self.finish_session(&args).await;
+                return Ok(());
+            }
+
+            // End the session once its time limit is reached
+            if let Some(max_secs) = args.max_session_duration_secs {
+                if self
+                    .session_stats
+                    .started_at
+                    .elapsed()
+                    .as_secs()
+                    .ge(&max_secs)
+                {
+                    println!(
+                        "{}Session time limit of {}s reached, shutting down",
+                        self.log_prefix, max_secs
+                    );
+                    self.finish_session(&args).await;
+                    return Ok(());
+                }
+            }
+
+            if args.pause_on_battery {
+                self.wait_for_ac_power().await;
+            }
+
+            // Pick up a reloaded keypair (--keypair-watch), registering it if needed
+            let reloaded = self.signer().pubkey().ne(&signer.pubkey());
+            if reloaded {
+                signer = self.signer();
+                self.register().await;
+            }
+
             // Fetch account state
-            let treasury = get_treasury(&self.rpc_client).await;
+            let treasury = get_treasury(&self.rpc_client, &self.addresses.treasury).await;
             let proof = get_proof(&self.rpc_client, signer.pubkey()).await;
+            if reloaded && !check_proof_authority(&proof, &signer.pubkey()) {
+                std::process::exit(EXIT_PROOF_AUTHORITY_MISMATCH);
+            }
+            self.session_stats.ore_earned.store(
+                proof.total_rewards.saturating_sub(initial_total_rewards),
+                Ordering::Relaxed,
+            );
             let rewards =
                 (proof.claimable_rewards as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
             let reward_rate =
                 (treasury.reward_rate as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
 
-            println!("Claimable: {} ORE", rewards);
-            println!("Reward rate: {} ORE", reward_rate);
+            println!("{}Claimable: {} ORE", self.log_prefix, rewards);
+            println!("{}Reward rate: {} ORE", self.log_prefix, reward_rate);
             println!();
 
-            println!("Mining for a valid hash...");
-            let (next_hash, nonce) =
-                self.find_next_hash_par(proof.hash.into(), treasury.difficulty.into(), threads);
-            println!(
-                "found solution: hash={}, nonce={nonce}",
-                next_hash.to_string()
+            // Check how long ago the proof was last updated
+            if let Some(max_age) = args.max_proof_age_secs {
+                if let Some(age) = self.proof_age_secs(signer.pubkey()).await {
+                    if age.lt(&1) {
+                        println!(
+                            "{}Proof was just updated, waiting for it to settle...",
+                            self.log_prefix
+                        );
+                        tokio::time::sleep(Duration::from_millis(PROOF_SETTLE_DELAY)).await;
+                        continue;
+                    }
+                    if age.gt(&(max_age as i64)) {
+                        println!(
+                            "{}WARNING: Proof was last updated {}s ago (over --max-proof-age-secs {}s)", self.log_prefix, age, max_age
+                        );
+                    }
+                }
+            }
+
+            println!("{}Mining for a valid hash...", self.log_prefix);
+            let hashes_before = self.session_stats.hashes.load(Ordering::Relaxed);
+            let hashing_started_at = Instant::now();
+            let solution = self.find_next_hash_par(
+                proof.hash.into(),
+                treasury.difficulty.into(),
+                &args,
+                max_nonce,
+            );
+            self.session_stats.record_hash_rate(
+                self.session_stats
+                    .hashes
+                    .load(Ordering::Relaxed)
+                    .saturating_sub(hashes_before),
+                hashing_started_at.elapsed(),
             );
+            if self.shutdown.load(Ordering::Relaxed) {
+                self.finish_session(&args).await;
+                return Ok(());
+            }
+            let Some((next_hash, nonce)) = solution else {
+                self.finish_session(&args).await;
+                anyhow::bail!(
+                    "No solution found in the nonce range [0, {}], stopping (see --max-nonce-u32)",
+                    max_nonce
+                );
+            };
+            SolutionLog {
+                nonce,
+                hash: match args.solution_log_format {
+                    ReportFormat::Text => next_hash.to_string(),
+                    _ => format!(
+                        "0x{}",
+                        next_hash
+                            .to_bytes()
+                            .iter()
+                            .map(|b| format!("{:02x}", b))
+                            .collect::<String>()
+                    ),
+                },
+                leading_zeros: leading_zeros(&next_hash),
+                reward_estimate: treasury.reward_rate,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }
+            .print(args.solution_log_format, &self.log_prefix, first_solution);
+            first_solution = false;
+            self.events.emit(Event::SolutionFound {
+                hash: next_hash.to_string(),
+                nonce,
+            });
             println!();
 
+            // Hand the solution to the external submitter
+            let mut landed = false;
+            if let Some(sink) = solution_sink.as_ref() {
+                sink.send(&Solution {
+                    authority: signer.pubkey().to_string(),
+                    proof: proof_pubkey(signer.pubkey()).to_string(),
+                    challenge: KeccakHash::from(proof.hash).to_string(),
+                    hash: next_hash.to_string(),
+                    nonce,
+                })
+                .await;
+                if args.sink_only {
+                    println!(
+                        "{}Solution sent, waiting for the external submitter to land it...",
+                        self.log_prefix
+                    );
+                    landed = self
+                        .wait_for_external_submission(signer.pubkey(), proof.hash)
+                        .await;
+                }
+            }
+
             // Submit mine tx.
             // Use busses randomly so on each epoch, transactions don't pile on the same busses
-            println!("Submitting hash for validation...");
+            if !args.sink_only {
+                println!("{}Submitting hash for validation...", self.log_prefix);
+            }
+            let mut failed_submissions = 0;
             'submit: loop {
+                // Leave submission to the external submitter
+                if args.sink_only {
+                    break 'submit;
+                }
+
                 // Double check we're submitting for the right challenge
                 let proof_ = get_proof(&self.rpc_client, signer.pubkey()).await;
                 if proof_.hash.ne(&proof.hash) {
-                    println!("Hash already validated! An earlier transaction must have landed.");
+                    println!(
+                        "{}Hash already validated! An earlier transaction must have landed.",
+                        self.log_prefix
+                    );
+                    landed = true;
+                    break 'submit;
+                }
+
+                // Give up on a --once round instead of retrying forever
+                if args.once && failed_submissions.ge(&ONCE_SUBMIT_ATTEMPTS) {
+                    println!(
+                        "{}Submission failed {} times, giving up on the round",
+                        self.log_prefix, failed_submissions
+                    );
                     break 'submit;
                 }
 
                 // Reset epoch, if needed
-                let treasury = get_treasury(&self.rpc_client).await;
+                let treasury = get_treasury(&self.rpc_client, &self.addresses.treasury).await;
                 let clock = get_clock_account(&self.rpc_client).await;
                 let threshold = treasury.last_reset_at.saturating_add(EPOCH_DURATION);
                 if clock.unix_timestamp.ge(&threshold) {
                     // There are a lot of miners right now, so randomly select into submitting tx
                     if rng.gen_range(0..RESET_ODDS).eq(&0) {
-                        println!("Sending epoch reset transaction...");
+                        println!("{}Sending epoch reset transaction...", self.log_prefix);
                         let cu_limit_ix =
                             ComputeBudgetInstruction::set_compute_unit_limit(CU_LIMIT_RESET);
-                        let cu_price_ix =
-                            ComputeBudgetInstruction::set_compute_unit_price(self.priority_fee);
+                        let cu_price_ix = ComputeBudgetInstruction::set_compute_unit_price(
+                            self.get_priority_fee().await,
+                        );
                         let reset_ix = ore::instruction::reset(signer.pubkey());
                         self.send_and_confirm(&[cu_limit_ix, cu_price_ix, reset_ix], false, true)
                             .await
@@ -75,47 +420,276 @@ impl Miner {
                     }
                 }
 
+                // Hold off submitting while fees exceed the budget
+                if let Some(max_fee) = args.max_fee_per_round_lamports {
+                    let fee = self.estimate_fee(CU_LIMIT_MINE).await;
+                    if fee.gt(&max_fee) {
+                        println!(
+                            "{}Round skipped: expected fee {} lamports exceeds budget of {} lamports", self.log_prefix, fee, max_fee
+                        );
+                        self.events.emit(Event::RoundSkipped {
+                            reason: format!("fee {} exceeds budget {}", fee, max_fee),
+                        });
+                        tokio::time::sleep(Duration::from_millis(FEE_BUDGET_DELAY)).await;
+                        continue 'submit;
+                    }
+                }
+
+                // Hold off submitting while the round is unprofitable
+                if let (Some(min_profit), Some(ore_price)) =
+                    (args.min_profit_lamports, args.ore_price_lamports)
+                {
+                    let fee = self.estimate_fee(CU_LIMIT_MINE).await;
+                    let reward = ((treasury.reward_rate as u128).saturating_mul(ore_price as u128)
+                        / ore::ONE_ORE as u128) as u64;
+                    let profit = reward as i64 - fee as i64;
+                    if profit.lt(&(min_profit as i64)) {
+                        skipped_rounds += 1;
+                        println!(
+                            "{}Round skipped: reward {} lamports - fee {} lamports = {} lamports profit, below minimum of {} lamports ({} skipped so far)", self.log_prefix, reward, fee, profit, min_profit, skipped_rounds
+                        );
+                        self.events.emit(Event::RoundSkipped {
+                            reason: format!("profit {} below minimum {}", profit, min_profit),
+                        });
+                        tokio::time::sleep(Duration::from_millis(FEE_BUDGET_DELAY)).await;
+                        continue 'submit;
+                    }
+                }
+
                 // Submit request.
-                let bus = self.find_bus_id(treasury.reward_rate).await;
-                let bus_rewards = (bus.rewards as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
-                println!("Sending on bus {} ({} ORE)", bus.id, bus_rewards);
+                let bus_id = if args.skip_bus_check {
+                    let bus_id = rng.gen_range(0..BUS_COUNT);
+                    println!("{}Sending on bus {} (unchecked)", self.log_prefix, bus_id);
+                    bus_id
+                } else {
+                    let bus = self
+                        .find_bus_id(
+                            treasury.reward_rate,
+                            args.filter_bus_by_reward,
+                            bus_cache.take(),
+                        )
+                        .await;
+                    let bus_rewards =
+                        (bus.rewards as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
+                    println!(
+                        "{}Sending on bus {} ({} ORE)",
+                        self.log_prefix, bus.id, bus_rewards
+                    );
+                    bus.id as usize
+                };
+                let priority_fee = self.get_priority_fee().await;
                 let cu_limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(CU_LIMIT_MINE);
-                let cu_price_ix =
-                    ComputeBudgetInstruction::set_compute_unit_price(self.priority_fee);
+                let cu_price_ix = ComputeBudgetInstruction::set_compute_unit_price(priority_fee);
                 let ix_mine = ore::instruction::mine(
                     signer.pubkey(),
-                    BUS_ADDRESSES[bus.id as usize],
+                    self.addresses.busses[bus_id],
                     next_hash.into(),
                     nonce,
                 );
-                match self
+                let res = self
                     .send_and_confirm(&[cu_limit_ix, cu_price_ix, ix_mine], false, false)
-                    .await
-                {
+                    .await;
+
+                // Refresh the bus cache for the next submission
+                if args.pre_load_buses && !args.skip_bus_check {
+                    bus_cache = Some(self.fetch_busses().await);
+                }
+
+                match res {
                     Ok(sig) => {
-                        println!("Success: {}", sig);
+                        println!("{}Success: {}", self.log_prefix, sig);
+                        self.on_submission_succeeded();
+                        self.session_stats.rounds.fetch_add(1, Ordering::Relaxed);
+                        self.wait_for_new_challenge(signer.pubkey(), proof.hash)
+                            .await;
+                        landed = true;
                         break;
                     }
                     Err(err) => {
-                        println!("tx failed, error: {err}");
+                        println!("{}tx failed, error: {err}", self.log_prefix);
+                        self.on_submission_failed(&err.to_string());
+                        failed_submissions += 1;
                     }
                 }
             }
+
+            // Exit after a single round, if requested
+            if args.once {
+                let proof = get_proof(&self.rpc_client, signer.pubkey()).await;
+                let rewards =
+                    (proof.claimable_rewards as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64));
+                println!(
+                    "{}Round complete. Claimable: {} ORE",
+                    self.log_prefix, rewards
+                );
+                if let ReportFormat::Json = args.mining_report_format {
+                    self.session_stats.summary().print();
+                }
+                if !landed {
+                    anyhow::bail!("The round's solution did not land");
+                }
+                return Ok(());
+            }
         }
     }
 
-    async fn find_bus_id(&self, reward_rate: u64) -> Bus {
-        let mut rng = rand::thread_rng();
+    /// Waits for the proof's challenge to advance after a confirmed submission,
+    /// so the next round doesn't mine against a stale challenge.
+    async fn wait_for_new_challenge(&self, authority: Pubkey, challenge: Hash) {
+        for _ in 0..CHALLENGE_REFRESH_RETRIES {
+            let proof = get_proof(&self.rpc_client, authority).await;
+            if proof.hash.ne(&challenge) {
+                return;
+            }
+            println!("{}WARNING: Proof challenge unchanged after a confirmed submission (possible RPC indexing lag), refreshing...", self.log_prefix);
+            tokio::time::sleep(Duration::from_millis(CHALLENGE_REFRESH_DELAY)).await;
+        }
+    }
+
+    /// Waits for the proof's challenge to advance once an external submitter lands the
+    /// solution, giving up after `SINK_SUBMISSION_TIMEOUT` so mining can resume.
+    /// Returns whether the solution landed.
+    async fn wait_for_external_submission(&self, authority: Pubkey, challenge: Hash) -> bool {
+        let started_at = Instant::now();
+        while started_at.elapsed().as_secs().lt(&SINK_SUBMISSION_TIMEOUT) {
+            let proof = get_proof(&self.rpc_client, authority).await;
+            if proof.hash.ne(&challenge) {
+                println!("{}Solution landed", self.log_prefix);
+                self.session_stats.rounds.fetch_add(1, Ordering::Relaxed);
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(CHALLENGE_REFRESH_DELAY)).await;
+        }
+        println!(
+            "{}WARNING: Solution not landed after {}s, mining the same challenge again",
+            self.log_prefix, SINK_SUBMISSION_TIMEOUT
+        );
+        false
+    }
+
+    /// Returns whether the RPC is connected to mainnet-beta, judged by its genesis hash.
+    async fn is_mainnet(&self) -> bool {
+        self.rpc_client
+            .get_genesis_hash()
+            .await
+            .is_ok_and(|hash| hash.to_string().eq(MAINNET_GENESIS_HASH))
+    }
+
+    /// Claims rewards if `--claim-on-exit` is set and prints a summary of the session.
+    async fn finish_session(&self, args: &MineArgs) {
+        if args.claim_on_exit {
+            let proof = get_proof(&self.rpc_client, self.signer().pubkey()).await;
+            self.claim_rewards(proof.claimable_rewards).await;
+        }
+        match args.mining_report_format {
+            ReportFormat::Json => self.session_stats.summary().print(),
+            _ => self
+                .session_stats
+                .report()
+                .print(ReportFormat::Text, &self.log_prefix, true),
+        }
+    }
+
+    /// Returns the seconds since the proof account was last written to, judged by the
+    /// block time of its latest transaction relative to the cluster clock.
+    async fn proof_age_secs(&self, authority: Pubkey) -> Option<i64> {
+        let signatures = self
+            .rpc_client
+            .get_signatures_for_address_with_config(
+                &proof_pubkey(authority),
+                GetConfirmedSignaturesForAddress2Config {
+                    limit: Some(1),
+                    ..Default::default()
+                },
+            )
+            .await
+            .ok()?;
+        let last_updated_at = signatures.first()?.block_time?;
+        let clock = get_clock_account(&self.rpc_client).await;
+        Some(clock.unix_timestamp.saturating_sub(last_updated_at))
+    }
+
+    /// Picks a bus to submit on, starting from the `preloaded` bus states if given
+    /// (--pre-load-buses) and re-fetching them while none is eligible.
+    async fn find_bus_id(
+        &self,
+        reward_rate: u64,
+        min_rewards: Option<u64>,
+        mut preloaded: Option<Vec<Bus>>,
+    ) -> Bus {
         loop {
-            let bus_id = rng.gen_range(0..BUS_COUNT);
-            if let Ok(bus) = self.get_bus(bus_id).await {
-                if bus.rewards.gt(&reward_rate.saturating_mul(4)) {
-                    return bus;
-                }
+            // Pick randomly among the busses with enough rewards
+            let busses = match preloaded.take() {
+                Some(busses) => busses,
+                None => self.fetch_busses().await,
+            };
+            let funded: Vec<Bus> = busses
+                .iter()
+                .filter(|bus| bus.rewards.gt(&reward_rate.saturating_mul(4)))
+                .copied()
+                .collect();
+            let eligible: Vec<Bus> = funded
+                .iter()
+                .filter(|bus| min_rewards.is_none_or(|min| bus.rewards.ge(&min)))
+                .copied()
+                .collect();
+            if let Some(bus) = eligible.choose(&mut rand::thread_rng()) {
+                return *bus;
             }
+
+            // Fall back to the richest funded bus when --filter-bus-by-reward excludes them all
+            if let (Some(min_rewards), Some(bus)) =
+                (min_rewards, funded.iter().max_by_key(|bus| bus.rewards))
+            {
+                println!(
+                    "WARNING: No bus has at least {} ORE, using bus {} with the most rewards",
+                    (min_rewards as f64) / (10f64.powf(ore::TOKEN_DECIMALS as f64)),
+                    bus.id
+                );
+                return *bus;
+            }
+            tokio::time::sleep(Duration::from_millis(BUS_CHECK_DELAY)).await;
         }
     }
 
+    /// Watches the hash counter while workers run. Returns `true` if no hashes were
+    /// computed for `timeout_secs`, or `false` once all workers have exited.
+    fn watch_workers(
+        &self,
+        thread_handles: &[std::thread::JoinHandle<()>],
+        timeout_secs: u64,
+    ) -> bool {
+        let mut hashes = self.session_stats.hashes.load(Ordering::Relaxed);
+        let mut last_progress = Instant::now();
+        while !thread_handles.iter().all(|handle| handle.is_finished()) {
+            std::thread::sleep(Duration::from_millis(WATCHDOG_POLL_DELAY));
+            let current = self.session_stats.hashes.load(Ordering::Relaxed);
+            if current.ne(&hashes) {
+                hashes = current;
+                last_progress = Instant::now();
+            } else if last_progress.elapsed().as_secs().ge(&timeout_secs) {
+                println!(
+                    "ERROR: Hash rate has been zero for {}s, mining workers may be stuck",
+                    timeout_secs
+                );
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Fetches all busses concurrently, skipping any that fail to load.
+    async fn fetch_busses(&self) -> Vec<Bus> {
+        let mut futures: FuturesUnordered<_> = (0..BUS_COUNT).map(|id| self.get_bus(id)).collect();
+        let mut busses = vec![];
+        while let Some(res) = futures.next().await {
+            if let Ok(bus) = res {
+                busses.push(bus);
+            }
+        }
+        busses
+    }
+
     fn _find_next_hash(&self, hash: KeccakHash, difficulty: KeccakHash) -> (KeccakHash, u64) {
         let signer = self.signer();
         let mut next_hash: KeccakHash;
@@ -129,7 +703,7 @@ impl Miner {
             if next_hash.le(&difficulty) {
                 break;
             } else {
-                println!("Invalid hash: {} Nonce: {:?}", next_hash.to_string(), nonce);
+                println!("Invalid hash: {} Nonce: {:?}", next_hash, nonce);
             }
             nonce += 1;
         }
@@ -140,8 +714,14 @@ impl Miner {
         &self,
         hash: KeccakHash,
         difficulty: KeccakHash,
-        threads: u64,
-    ) -> (KeccakHash, u64) {
+        args: &MineArgs,
+        max_nonce: u64,
+    ) -> Option<(KeccakHash, u64)> {
+        let threads = args.threads;
+        let min_leading_zeros = args.reject_small_hashes.unwrap_or(0);
+        let pin_threads = args.pin_threads;
+        let nice = args.nice;
+        let hasher = args.hash_algorithm.hasher();
         let found_solution = Arc::new(AtomicBool::new(false));
         let solution = Arc::new(Mutex::<(KeccakHash, u64)>::new((
             KeccakHash::new_from_array([0; 32]),
@@ -149,58 +729,113 @@ impl Miner {
         )));
         let signer = self.signer();
         let pubkey = signer.pubkey();
-        let thread_handles: Vec<_> = (0..threads)
-            .map(|i| {
-                std::thread::spawn({
-                    let found_solution = found_solution.clone();
-                    let solution = solution.clone();
-                    move || {
-                        let n = u64::MAX.saturating_div(threads).saturating_mul(i);
-                        let mut next_hash: KeccakHash;
-                        let mut nonce: u64 = n;
-                        loop {
-                            next_hash = hashv(&[
-                                hash.to_bytes().as_slice(),
-                                pubkey.to_bytes().as_slice(),
-                                nonce.to_le_bytes().as_slice(),
-                            ]);
-                            if nonce % 10_000 == 0 {
-                                if found_solution.load(std::sync::atomic::Ordering::Relaxed) {
-                                    return;
+        let mut restarts = 0u64;
+        loop {
+            // Flags this generation of workers to stop when the watchdog restarts them
+            let stop = Arc::new(AtomicBool::new(false));
+            let thread_handles: Vec<_> = (0..threads)
+                .map(|i| {
+                    std::thread::spawn({
+                        let found_solution = found_solution.clone();
+                        let solution = solution.clone();
+                        let session_stats = self.session_stats.clone();
+                        let shutdown = self.shutdown.clone();
+                        let stop = stop.clone();
+                        let hasher = hasher.clone();
+                        move || {
+                            if pin_threads && !pin_to_core(worker_core(i)) {
+                                println!("Failed to pin worker {} to core {}", i, worker_core(i));
+                            }
+                            if let Some(nice) = nice {
+                                if cfg!(unix) && !set_niceness(nice) {
+                                    NICENESS_WARNING.call_once(|| {
+                                        println!("WARNING: Failed to set niceness to {}", nice);
+                                    });
                                 }
                             }
-                            if next_hash.le(&difficulty) {
-                                found_solution.store(true, std::sync::atomic::Ordering::Relaxed);
-                                let mut w_solution = solution.lock().expect("failed to lock mutex");
-                                *w_solution = (next_hash, nonce);
-                                return;
+                            let n = max_nonce.saturating_div(threads).saturating_mul(i);
+                            let mut next_hash: KeccakHash;
+                            let mut nonce: u64 = n;
+                            let mut preimage = [0u8; 72];
+                            preimage[..32].copy_from_slice(&hash.to_bytes());
+                            preimage[32..64].copy_from_slice(&pubkey.to_bytes());
+                            loop {
+                                preimage[64..].copy_from_slice(&nonce.to_le_bytes());
+                                next_hash = KeccakHash::new_from_array(hasher.hash(&preimage));
+                                if nonce.is_multiple_of(10_000) {
+                                    session_stats.hashes.fetch_add(10_000, Ordering::Relaxed);
+                                    if found_solution.load(Ordering::Relaxed)
+                                        || shutdown.load(Ordering::Relaxed)
+                                        || stop.load(Ordering::Relaxed)
+                                    {
+                                        return;
+                                    }
+                                }
+                                if next_hash.le(&difficulty) {
+                                    // Simulate a higher difficulty, if requested (--reject-small-hashes)
+                                    let zeros = leading_zeros(&next_hash);
+                                    if zeros.lt(&min_leading_zeros) {
+                                        println!(
+                                            "Rejected solution: hash={}, nonce={}, {} leading zero bits (want {})",
+                                            next_hash, nonce, zeros, min_leading_zeros
+                                        );
+                                        if nonce.ge(&max_nonce) {
+                                            return;
+                                        }
+                                        nonce += 1;
+                                        continue;
+                                    }
+                                    found_solution.store(true, Ordering::Relaxed);
+                                    let mut w_solution = solution.lock().expect("failed to lock mutex");
+                                    *w_solution = (next_hash, nonce);
+                                    return;
+                                }
+                                if nonce.ge(&max_nonce) {
+                                    return;
+                                }
+                                nonce += 1;
                             }
-                            nonce += 1;
                         }
-                    }
+                    })
                 })
-            })
-            .collect();
+                .collect();
 
-        for thread_handle in thread_handles {
-            thread_handle.join().unwrap();
+            // Restart the workers if they stop hashing (--watchdog-timeout)
+            let Some(timeout) = args.watchdog_timeout else {
+                for thread_handle in thread_handles {
+                    thread_handle.join().unwrap();
+                }
+                break;
+            };
+            let mut stalled = self.watch_workers(&thread_handles, timeout);
+            if !stalled {
+                for thread_handle in thread_handles {
+                    if thread_handle.join().is_err() {
+                        println!("ERROR: A mining worker panicked");
+                        stalled = true;
+                    }
+                }
+                stalled = stalled
+                    && !found_solution.load(Ordering::Relaxed)
+                    && !self.shutdown.load(Ordering::Relaxed);
+            }
+            if !stalled {
+                break;
+            }
+            stop.store(true, Ordering::Relaxed);
+            restarts += 1;
+            println!("Restarting mining workers (restart {})", restarts);
         }
 
+        if !found_solution.load(Ordering::Relaxed) {
+            return None;
+        }
         let r_solution = solution.lock().expect("Failed to get lock");
-        *r_solution
+        Some(*r_solution)
     }
 
     pub async fn get_ore_display_balance(&self) -> String {
-        let signer = self.signer();
-        let token_account_address = spl_associated_token_account::get_associated_token_address(
-            &signer.pubkey(),
-            &ore::MINT_ADDRESS,
-        );
-        match self
-            .rpc_client
-            .get_token_account(&token_account_address)
-            .await
-        {
+        match resolve_ata(&self.rpc_client, self.signer().pubkey()).await {
             Ok(token_account) => {
                 if let Some(token_account) = token_account {
                     token_account.token_amount.ui_amount_string
@@ -212,3 +847,126 @@ impl Miner {
         }
     }
 }
+
+/// Requests a clean shutdown of the mining loop on Ctrl-C, or exits right away with `--fast-exit`.
+/// A second Ctrl-C always exits immediately.
+async fn watch_shutdown(shutdown: Arc<AtomicBool>, fast_exit: bool) {
+    if tokio::signal::ctrl_c().await.is_err() {
+        return;
+    }
+    if fast_exit {
+        std::process::exit(0);
+    }
+    println!("Shutting down after the current step, press Ctrl-C again to exit immediately...");
+    shutdown.store(true, Ordering::Relaxed);
+    if tokio::signal::ctrl_c().await.is_ok() {
+        std::process::exit(0);
+    }
+}
+
+/// Returns a hash of the ORE program's executable data, or of the program account
+/// itself when it isn't deployed with the upgradeable loader. Returns `None` if either
+/// account could not be fetched, so a transient RPC error isn't mistaken for an upgrade.
+async fn program_hash(rpc_client: &RpcClient) -> Option<KeccakHash> {
+    let program = rpc_client.get_account(&ore::ID).await.ok()?;
+    if program.owner.ne(&bpf_loader_upgradeable::id()) {
+        return Some(hashv(&[&program.data]));
+    }
+    let (program_data_address, _) =
+        Pubkey::find_program_address(&[ore::ID.as_ref()], &bpf_loader_upgradeable::id());
+    let data = rpc_client
+        .get_account_data(&program_data_address)
+        .await
+        .ok()?;
+    Some(hashv(&[&data]))
+}
+
+/// Exits the process once the ORE program's executable changes (--halt-on-program-upgrade),
+/// so a supervisor can restart mining with an updated binary. Without an initial `hash`,
+/// the first successful check becomes the baseline.
+async fn watch_program_upgrade(
+    rpc_client: Arc<RpcClient>,
+    mut hash: Option<KeccakHash>,
+    interval_secs: u64,
+) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+        let Some(current) = program_hash(&rpc_client).await else {
+            continue;
+        };
+        if current.ne(hash.get_or_insert(current)) {
+            println!("ORE program was upgraded, halting mining");
+            std::process::exit(EXIT_PROGRAM_UPGRADED);
+        }
+    }
+}
+
+/// Returns the CPU core a mining worker is pinned to with `--pin-threads`.
+fn worker_core(worker: u64) -> usize {
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    worker as usize % cores
+}
+
+/// Pins the current thread to the given CPU core. Returns whether pinning succeeded.
+#[cfg(target_os = "linux")]
+fn pin_to_core(core: usize) -> bool {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core, &mut set);
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_to_core(_core: usize) -> bool {
+    false
+}
+
+/// Sets the scheduling niceness of the current thread. Returns whether it succeeded.
+/// Linux schedules threads individually, so this leaves the rest of the process alone.
+#[cfg(target_os = "linux")]
+fn set_niceness(nice: i32) -> bool {
+    unsafe {
+        let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+        libc::setpriority(libc::PRIO_PROCESS, tid, nice) == 0
+    }
+}
+
+/// Sets the scheduling niceness of the whole process, since other unixes have no
+/// per-thread niceness. Returns whether it succeeded.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn set_niceness(nice: i32) -> bool {
+    unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) == 0 }
+}
+
+#[cfg(not(unix))]
+fn set_niceness(_nice: i32) -> bool {
+    false
+}
+
+/// Returns the number of leading zero bits of a hash.
+fn leading_zeros(hash: &KeccakHash) -> u32 {
+    let mut zeros = 0;
+    for byte in hash.to_bytes() {
+        zeros += byte.leading_zeros();
+        if byte.ne(&0) {
+            break;
+        }
+    }
+    zeros
+}
+
+/// Checks that the proof fetched for `signer` is actually owned by it, so a mismatched
+/// keypair fails fast instead of submitting transactions that can never land.
+fn check_proof_authority(proof: &Proof, signer: &Pubkey) -> bool {
+    if proof.authority.ne(signer) {
+        println!(
+            "ERROR: Proof account {} belongs to {}, not the signer {}. Check your --keypair.",
+            proof_pubkey(*signer),
+            format_pubkey(&proof.authority),
+            format_pubkey(signer)
+        );
+        return false;
+    }
+    true
+}
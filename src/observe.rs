@@ -0,0 +1,24 @@
+use std::{io::IsTerminal, time::Duration};
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+
+use crate::{balance::Balances, ObserveArgs};
+
+/// Prints the balances of any address. Only reads from the RPC, so no keypair is loaded.
+pub async fn observe(rpc_client: &RpcClient, args: ObserveArgs) {
+    if !args.watch {
+        Balances::fetch(rpc_client, args.address).await.print(false);
+        return;
+    }
+    println!(
+        "Watching {} every {}s, press Ctrl-C to stop",
+        args.address, args.interval_secs
+    );
+    let is_terminal = std::io::stdout().is_terminal();
+    loop {
+        Balances::fetch(rpc_client, args.address)
+            .await
+            .print(is_terminal);
+        tokio::time::sleep(Duration::from_secs(args.interval_secs)).await;
+    }
+}
@@ -0,0 +1,43 @@
+use base64::Engine;
+use solana_sdk::transaction::Transaction;
+
+use crate::Miner;
+
+impl Miner {
+    /// Prints a signed transaction as base64 for `--sign-only`, or writes it to `--unsigned-tx-output`.
+    pub fn write_signed_transaction(&self, tx: &Transaction) -> anyhow::Result<()> {
+        let serialized_tx = bincode::serialize(tx)?;
+        let encoded_tx = base64::engine::general_purpose::STANDARD.encode(serialized_tx);
+        match self.unsigned_tx_output.as_ref() {
+            Some(path) => {
+                std::fs::write(path, &encoded_tx)?;
+                println!("Wrote signed transaction to {}", path);
+            }
+            None => println!("{}", encoded_tx),
+        }
+        Ok(())
+    }
+
+    /// Submits a base64 transaction produced by `--sign-only` and waits for it to confirm.
+    pub async fn submit_signed_tx(&self, path: &str) {
+        let tx = match read_signed_transaction(path) {
+            Ok(tx) => tx,
+            Err(err) => {
+                println!("Failed to read transaction from {}: {}", path, err);
+                return;
+            }
+        };
+        println!("Submitting transaction {}...", tx.signatures[0]);
+        match self.rpc_client.send_and_confirm_transaction(&tx).await {
+            Ok(sig) => println!("Transaction landed! {}", sig),
+            Err(err) => println!("Error: {:?}", err),
+        }
+    }
+}
+
+/// Reads a base64 transaction written by `--sign-only`.
+pub fn read_signed_transaction(path: &str) -> anyhow::Result<Transaction> {
+    let encoded_tx = std::fs::read_to_string(path)?;
+    let serialized_tx = base64::engine::general_purpose::STANDARD.decode(encoded_tx.trim())?;
+    Ok(bincode::deserialize(&serialized_tx)?)
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use crate::Miner;
+
+// Delay between power state checks while paused
+const POWER_POLL_DELAY: u64 = 30_000;
+
+/// Returns whether the system is running on battery power,
+/// or `None` if the power state cannot be determined on this platform.
+#[cfg(all(feature = "battery", target_os = "linux"))]
+pub fn on_battery() -> Option<bool> {
+    let mut found_mains = false;
+    for entry in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let path = entry.path();
+        let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+        if kind.trim().eq("Mains") {
+            found_mains = true;
+            let online = std::fs::read_to_string(path.join("online")).unwrap_or_default();
+            if online.trim().eq("1") {
+                return Some(false);
+            }
+        }
+    }
+    found_mains.then_some(true)
+}
+
+#[cfg(all(feature = "battery", target_os = "macos"))]
+pub fn on_battery() -> Option<bool> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()?;
+    let output = String::from_utf8_lossy(&output.stdout);
+    Some(output.contains("'Battery Power'"))
+}
+
+#[cfg(not(all(feature = "battery", any(target_os = "linux", target_os = "macos"))))]
+pub fn on_battery() -> Option<bool> {
+    None
+}
+
+impl Miner {
+    /// Blocks while the system is on battery power.
+    pub async fn wait_for_ac_power(&self) {
+        if on_battery().ne(&Some(true)) {
+            return;
+        }
+        println!("Running on battery power, pausing mining...");
+        while on_battery().eq(&Some(true)) {
+            tokio::time::sleep(Duration::from_millis(POWER_POLL_DELAY)).await;
+        }
+        println!("AC power restored, resuming mining");
+    }
+}
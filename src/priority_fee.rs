@@ -0,0 +1,278 @@
+use std::time::Instant;
+
+use clap::ValueEnum;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+
+use crate::Miner;
+
+/// Base fee charged per transaction signature, in lamports.
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5000;
+
+// Compute units the runtime allots each instruction when a transaction sets no limit
+const DEFAULT_CU_LIMIT_PER_INSTRUCTION: u32 = 200_000;
+
+// How long a fetched percentile or oracle fee is reused before querying again
+const PERCENTILE_FEE_TTL: u64 = 10;
+
+/// Returns the total fee (in lamports) of a single-signature transaction
+/// with the given CU limit and priority fee (in microlamports).
+pub fn transaction_fee(cu_limit: u32, priority_fee: u64) -> u64 {
+    let priority_fee = (cu_limit as u128)
+        .saturating_mul(priority_fee as u128)
+        .div_ceil(1_000_000) as u64;
+    LAMPORTS_PER_SIGNATURE.saturating_add(priority_fee)
+}
+
+/// How `--priority-fee` and the related fee flags are denominated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum FeeUnit {
+    /// Microlamports per compute unit, the unit the runtime charges in
+    #[default]
+    PerCu,
+    /// Microlamports for the whole transaction, divided by its compute unit limit
+    PerTx,
+}
+
+impl Miner {
+    /// Converts a priority fee in `--fee-unit` to microlamports per compute unit.
+    pub fn cu_price(&self, cu_limit: u32, priority_fee: u64) -> u64 {
+        match self.fee_unit {
+            FeeUnit::PerCu => priority_fee,
+            FeeUnit::PerTx => priority_fee.div_ceil(cu_limit.max(1) as u64),
+        }
+    }
+
+    /// Rewrites the compute unit price of the given instructions from `--fee-unit` to
+    /// microlamports per compute unit, using the transaction's compute unit limit.
+    pub fn resolve_fee_unit(&self, ixs: &[Instruction]) -> Vec<Instruction> {
+        if self.fee_unit.eq(&FeeUnit::PerCu) {
+            return ixs.to_vec();
+        }
+        let cu_limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(0);
+        let cu_price_ix = ComputeBudgetInstruction::set_compute_unit_price(0);
+        let is_compute_budget = |ix: &&Instruction| ix.program_id.eq(&cu_limit_ix.program_id);
+        let cu_limit = ixs
+            .iter()
+            .filter(is_compute_budget)
+            .find(|ix| ix.data.first().eq(&cu_limit_ix.data.first()))
+            .and_then(|ix| Some(u32::from_le_bytes(ix.data.get(1..5)?.try_into().ok()?)))
+            .unwrap_or_else(|| {
+                let count = ixs.iter().filter(|ix| !is_compute_budget(ix)).count() as u32;
+                count.saturating_mul(DEFAULT_CU_LIMIT_PER_INSTRUCTION)
+            });
+        let Some(priority_fee) = ixs
+            .iter()
+            .filter(is_compute_budget)
+            .find(|ix| ix.data.first().eq(&cu_price_ix.data.first()))
+            .and_then(|ix| Some(u64::from_le_bytes(ix.data.get(1..9)?.try_into().ok()?)))
+        else {
+            return ixs.to_vec();
+        };
+        let cu_price = self.cu_price(cu_limit, priority_fee);
+        println!(
+            "Priority fee: {} microlamports per tx over {} CUs = {} microlamports per CU",
+            priority_fee, cu_limit, cu_price
+        );
+        reprice_instructions(ixs, cu_price)
+    }
+
+    /// Returns the priority fee (in microlamports) to attach to the next transaction.
+    pub async fn get_priority_fee(&self) -> u64 {
+        if let Some(url) = self.fee_oracle.as_ref() {
+            if let Some(fee) = self.get_oracle_fee(url).await {
+                return fee.max(self.min_priority_fee);
+            }
+        }
+        if let Some(percentile) = self.fee_percentile {
+            if let Some(fee) = self.get_percentile_fee(percentile).await {
+                return fee.max(self.min_priority_fee);
+            }
+        }
+        let lock = self.current_priority_fee.lock().unwrap();
+        lock.0
+    }
+
+    /// Estimates the total fee (in lamports) of a single-signature transaction with the given CU limit.
+    pub async fn estimate_fee(&self, cu_limit: u32) -> u64 {
+        transaction_fee(
+            cu_limit,
+            self.cu_price(cu_limit, self.get_priority_fee().await),
+        )
+    }
+
+    /// Adjusts the current priority fee after a transaction landed. A fee raised by
+    /// `--priority-fee-increase-on-fail` is reset to `--priority-fee`; otherwise, if
+    /// `--priority-fee-decay` is set, it is halved for every half-life elapsed since the
+    /// last adjustment, down to `--min-priority-fee`.
+    pub fn on_transaction_landed(&self) {
+        let mut lock = self.current_priority_fee.lock().unwrap();
+        let (fee, updated_at) = *lock;
+
+        // Undo any increases from failed landings
+        if fee.gt(&self.priority_fee) {
+            println!(
+                "Priority fee reset: {} -> {} microlamports",
+                fee, self.priority_fee
+            );
+            *lock = (self.priority_fee, Instant::now());
+            return;
+        }
+
+        let Some(half_life) = self.priority_fee_decay else {
+            return;
+        };
+        if half_life == 0 {
+            return;
+        }
+        let elapsed = updated_at.elapsed().as_secs_f64();
+        let factor = 0.5f64.powf(elapsed / half_life as f64);
+        let decayed = ((fee as f64 * factor) as u64).max(self.min_priority_fee.min(fee));
+        if decayed != fee {
+            println!("Priority fee decayed: {} -> {} microlamports", fee, decayed);
+        }
+        *lock = (decayed, Instant::now());
+    }
+
+    /// Adjusts the current priority fee after a failed landing. A fee lowered by
+    /// `--priority-fee-decay` is reset to `--priority-fee`; otherwise it is multiplied by
+    /// `--priority-fee-increase-on-fail`, up to `--max-priority-fee-cap`.
+    pub fn on_transaction_failed(&self) {
+        let mut lock = self.current_priority_fee.lock().unwrap();
+        let fee = lock.0;
+
+        // Undo any decay from earlier landings
+        if fee.lt(&self.priority_fee) {
+            println!(
+                "Priority fee reset: {} -> {} microlamports",
+                fee, self.priority_fee
+            );
+            *lock = (self.priority_fee, Instant::now());
+            return;
+        }
+
+        if self.priority_fee_increase_on_fail.le(&1.0) {
+            return;
+        }
+        let mut increased = (fee.max(1) as f64 * self.priority_fee_increase_on_fail) as u64;
+        if let Some(cap) = self.max_priority_fee_cap {
+            increased = increased.min(cap);
+        }
+        if increased != fee {
+            println!(
+                "Priority fee increased: {} -> {} microlamports",
+                fee, increased
+            );
+        }
+        *lock = (increased, Instant::now());
+    }
+
+    /// Fetches a recommended fee from a `--fee-oracle` endpoint.
+    /// The oracle is called with the Helius `getPriorityFeeEstimate` JSON-RPC shape:
+    /// `{"method": "getPriorityFeeEstimate", "params": [{"accountKeys": [...], "options": {"recommended": true}}]}`
+    /// and must respond with `{"result": {"priorityFeeEstimate": <microlamports>}}`.
+    async fn get_oracle_fee(&self, url: &str) -> Option<u64> {
+        // Return cached value, if fresh
+        if let Some((fee, fetched_at)) = *self.oracle_fee_cache.lock().unwrap() {
+            if fetched_at.elapsed().as_secs().lt(&PERCENTILE_FEE_TTL) {
+                return Some(fee);
+            }
+        }
+
+        let account_keys: Vec<String> = self
+            .priority_fee_accounts()
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getPriorityFeeEstimate",
+            "params": [{
+                "accountKeys": account_keys,
+                "options": { "recommended": true },
+            }],
+        });
+        let res = match self.http_client.post(url).json(&body).send().await {
+            Ok(res) => res,
+            Err(err) => {
+                println!("Failed to reach fee oracle: {:?}", err);
+                return None;
+            }
+        };
+        let res = match res.json::<serde_json::Value>().await {
+            Ok(res) => res,
+            Err(err) => {
+                println!("Failed to parse fee oracle response: {:?}", err);
+                return None;
+            }
+        };
+        let Some(fee) = res
+            .pointer("/result/priorityFeeEstimate")
+            .and_then(|fee| fee.as_f64())
+        else {
+            println!("Unexpected fee oracle response: {}", res);
+            return None;
+        };
+        let fee = fee.ceil() as u64;
+        *self.oracle_fee_cache.lock().unwrap() = Some((fee, Instant::now()));
+        Some(fee)
+    }
+
+    /// Returns the accounts whose recent fees drive the priority fee: the busses,
+    /// or the `--use-priority-fee-account` if set.
+    fn priority_fee_accounts(&self) -> Vec<Pubkey> {
+        match self.priority_fee_account {
+            Some(account) => vec![account],
+            None => self.addresses.busses.to_vec(),
+        }
+    }
+
+    async fn get_percentile_fee(&self, percentile: u8) -> Option<u64> {
+        // Return cached value, if fresh
+        if let Some((fee, fetched_at)) = *self.percentile_fee_cache.lock().unwrap() {
+            if fetched_at.elapsed().as_secs().lt(&PERCENTILE_FEE_TTL) {
+                return Some(fee);
+            }
+        }
+
+        // Fetch recent fees of the accounts mine transactions write-lock
+        let mut fees: Vec<u64> = match self
+            .rpc_client
+            .get_recent_prioritization_fees(&self.priority_fee_accounts())
+            .await
+        {
+            Ok(fees) => fees.iter().map(|f| f.prioritization_fee).collect(),
+            Err(err) => {
+                println!("Failed to fetch recent prioritization fees: {:?}", err);
+                return None;
+            }
+        };
+        if fees.is_empty() {
+            return None;
+        }
+        fees.sort_unstable();
+        let index = (fees.len() * percentile as usize)
+            .div_ceil(100)
+            .saturating_sub(1);
+        let fee = fees[index];
+        *self.percentile_fee_cache.lock().unwrap() = Some((fee, Instant::now()));
+        Some(fee)
+    }
+}
+
+/// Replaces the compute unit price of the given instructions with `priority_fee`.
+pub fn reprice_instructions(ixs: &[Instruction], priority_fee: u64) -> Vec<Instruction> {
+    let cu_price_ix = ComputeBudgetInstruction::set_compute_unit_price(priority_fee);
+    ixs.iter()
+        .map(|ix| {
+            if ix.program_id.eq(&cu_price_ix.program_id)
+                && ix.data.first().eq(&cu_price_ix.data.first())
+            {
+                cu_price_ix.clone()
+            } else {
+                ix.clone()
+            }
+        })
+        .collect()
+}
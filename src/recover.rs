@@ -0,0 +1,57 @@
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signer};
+
+use crate::{offline::read_signed_transaction, utils::format_pubkey, Miner, RecoverArgs};
+
+impl Miner {
+    pub async fn recover(&self, args: RecoverArgs) {
+        let mut tx = match read_signed_transaction(&args.tx_file) {
+            Ok(tx) => tx,
+            Err(err) => {
+                println!("Failed to read transaction from {}: {}", args.tx_file, err);
+                return;
+            }
+        };
+
+        if args.fresh_blockhash {
+            // Re-sign with a recent blockhash, so an expired transaction can land
+            let signer = self.signer();
+            let (hash, _) = match self.get_latest_blockhash().await {
+                Ok(blockhash) => blockhash,
+                Err(err) => {
+                    println!("Failed to fetch a recent blockhash: {}", err);
+                    return;
+                }
+            };
+            if let Err(err) = tx.try_sign(&[&signer], hash) {
+                println!(
+                    "Cannot re-sign the transaction with {}: {}",
+                    format_pubkey(&signer.pubkey()),
+                    err
+                );
+                return;
+            }
+        } else {
+            match self
+                .rpc_client
+                .is_blockhash_valid(&tx.message.recent_blockhash, CommitmentConfig::confirmed())
+                .await
+            {
+                Ok(false) => {
+                    println!(
+                        "The transaction's blockhash {} has expired, so it can no longer land. Run again with --fresh-blockhash to re-sign it",
+                        tx.message.recent_blockhash
+                    );
+                    return;
+                }
+                Ok(true) => {}
+                Err(err) => println!("Failed to check blockhash, submitting anyway: {:?}", err),
+            }
+        }
+
+        println!("Submitting transaction {}...", tx.signatures[0]);
+        match self.rpc_client.send_and_confirm_transaction(&tx).await {
+            Ok(sig) => println!("Transaction landed! {}", sig),
+            Err(err) => println!("Error: {:?}", err),
+        }
+    }
+}
@@ -1,6 +1,10 @@
-use solana_sdk::signature::Signer;
+use solana_sdk::{compute_budget::ComputeBudgetInstruction, signature::Signer};
 
-use crate::{utils::proof_pubkey, Miner};
+use crate::{
+    cu_limits::CU_LIMIT_REGISTER,
+    utils::{format_pubkey, proof_pubkey},
+    Miner,
+};
 
 impl Miner {
     pub async fn register(&self) {
@@ -12,9 +16,15 @@ impl Miner {
         }
 
         // Sign and send transaction.
-        println!("Generating challenge...");
+        println!(
+            "Generating challenge for {}...",
+            format_pubkey(&signer.pubkey())
+        );
+        let cu_limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(CU_LIMIT_REGISTER);
+        let cu_price_ix =
+            ComputeBudgetInstruction::set_compute_unit_price(self.get_priority_fee().await);
         let ix = ore::instruction::register(signer.pubkey());
-        self.send_and_confirm(&[ix], true, false)
+        self.send_and_confirm(&[cu_limit_ix, cu_price_ix, ix], false, false)
             .await
             .expect("Transaction failed");
     }
@@ -1,12 +1,20 @@
 use std::str::FromStr;
 
 use solana_program::pubkey::Pubkey;
-use solana_sdk::signature::Signer;
+use solana_sdk::commitment_config::CommitmentConfig;
 
-use crate::{utils::get_proof, Miner};
+use crate::{
+    utils::{get_proof_with_commitment, print_raw_account, proof_pubkey},
+    Miner,
+};
 
 impl Miner {
-    pub async fn rewards(&self, address: Option<String>) {
+    pub async fn rewards(
+        &self,
+        address: Option<String>,
+        commitment: CommitmentConfig,
+        raw_account: bool,
+    ) {
         let address = if let Some(address) = address {
             if let Ok(address) = Pubkey::from_str(&address) {
                 address
@@ -15,10 +23,27 @@ impl Miner {
                 return;
             }
         } else {
-            self.signer().pubkey()
+            let Some(address) = self.default_address() else {
+                return;
+            };
+            address
         };
-        let proof = get_proof(&self.rpc_client, address).await;
+        let proof = get_proof_with_commitment(&self.rpc_client, address, commitment).await;
         let amount = (proof.claimable_rewards as f64) / 10f64.powf(ore::TOKEN_DECIMALS as f64);
         println!("{:} ORE", amount);
+        if raw_account {
+            match self
+                .rpc_client
+                .get_account_with_commitment(&proof_pubkey(address), commitment)
+                .await
+            {
+                Ok(res) => {
+                    if let Some(account) = res.value {
+                        print_raw_account(&account.data);
+                    }
+                }
+                Err(err) => println!("Failed to fetch proof account: {:?}", err),
+            }
+        }
     }
 }
@@ -0,0 +1,111 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use solana_client::{
+    client_error::Result as ClientResult,
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::RpcClientConfig,
+    rpc_request::RpcRequest,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_rpc_client::http_sender::HttpSender;
+use solana_sdk::commitment_config::CommitmentConfig;
+use tokio::sync::Semaphore;
+
+// Timeout of a whole RPC request, matching the solana client's default
+const RPC_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An HTTP transport that caps the number of in-flight RPC calls (--max-concurrent-rpcs) and,
+/// with --log-rpc-calls, prints the method, response size and latency of every call.
+pub struct MinerRpcSender {
+    inner: HttpSender,
+    permits: Arc<Semaphore>,
+    log_rpc_calls: bool,
+}
+
+#[async_trait]
+impl RpcSender for MinerRpcSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> ClientResult<serde_json::Value> {
+        let _permit = self.permits.acquire().await.unwrap();
+        if !self.log_rpc_calls {
+            return self.inner.send(request, params).await;
+        }
+        let requested_at = chrono::Utc::now();
+        let started_at = Instant::now();
+        let res = self.inner.send(request, params).await;
+        let response_bytes = match res.as_ref() {
+            Ok(value) => value.to_string().len(),
+            Err(_) => 0,
+        };
+        log_rpc_call(
+            &request.to_string(),
+            requested_at,
+            started_at,
+            response_bytes,
+            res.is_err(),
+        );
+        res
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+/// Prints one RPC call for --log-rpc-calls, to stderr so --output json stays parseable.
+pub fn log_rpc_call(
+    method: &str,
+    requested_at: chrono::DateTime<chrono::Utc>,
+    started_at: Instant,
+    response_bytes: usize,
+    is_err: bool,
+) {
+    eprintln!(
+        "RPC {} at {}: {} bytes in {}ms{}",
+        method,
+        requested_at.to_rfc3339(),
+        response_bytes,
+        started_at.elapsed().as_millis(),
+        if is_err { " (error)" } else { "" }
+    );
+}
+
+/// Creates an RPC client, logging every call if `--log-rpc-calls` is set.
+/// Connecting gives up after `connect_timeout_ms` (--connect-timeout-ms), while
+/// requests keep the default timeout. Clients sharing `permits` share one concurrency limit.
+pub fn new_rpc_client(
+    url: String,
+    commitment: CommitmentConfig,
+    log_rpc_calls: bool,
+    connect_timeout_ms: u64,
+    permits: Arc<Semaphore>,
+) -> RpcClient {
+    let client = reqwest::Client::builder()
+        .default_headers(HttpSender::default_headers())
+        .timeout(RPC_REQUEST_TIMEOUT)
+        .pool_idle_timeout(RPC_REQUEST_TIMEOUT)
+        .connect_timeout(Duration::from_millis(connect_timeout_ms))
+        .build()
+        .expect("build rpc client");
+    let sender = HttpSender::new_with_client(url, client);
+    let config = RpcClientConfig::with_commitment(commitment);
+    RpcClient::new_sender(
+        MinerRpcSender {
+            inner: sender,
+            permits,
+            log_rpc_calls,
+        },
+        config,
+    )
+}
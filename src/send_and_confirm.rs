@@ -1,27 +1,54 @@
+use futures::{stream::FuturesUnordered, StreamExt};
+use ore::error::OreError;
 use solana_client::{
     client_error::{ClientError, ClientErrorKind, Result as ClientResult},
-    rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig, RpcTransactionConfig},
 };
-use solana_program::instruction::Instruction;
+use solana_program::instruction::{Instruction, InstructionError};
 use solana_sdk::{
     commitment_config::{CommitmentConfig, CommitmentLevel},
     compute_budget::ComputeBudgetInstruction,
     signature::{Signature, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, TransactionConfirmationStatus, UiTransactionEncoding,
+    UiTransactionStatusMeta,
+};
+use std::{
+    collections::HashSet,
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
 };
-use solana_transaction_status::{TransactionConfirmationStatus, UiTransactionEncoding};
-use std::time::Duration;
 
-use crate::Miner;
+use crate::{
+    cu_limits::{ore_instruction_kind, resize_cu_limit},
+    events::Event,
+    priority_fee::reprice_instructions,
+    utils::format_pubkey,
+    Miner,
+};
 
-const RPC_RETRIES: usize = 0;
 const SIMULATION_RETRIES: usize = 4;
 const GATEWAY_RETRIES: usize = 2;
 const CONFIRM_RETRIES: usize = 2;
 
-const CONFIRM_DELAY: u64 = 5000;
 const GATEWAY_DELAY: u64 = 2000;
 
+// Delay between checks while waiting for a transaction to finalize
+const FINALITY_POLL_DELAY: u64 = 2000;
+
+// Ore program errors, for matching custom error codes
+const ORE_ERRORS: [OreError; 7] = [
+    OreError::NotStarted,
+    OreError::NeedsReset,
+    OreError::ResetTooEarly,
+    OreError::HashInvalid,
+    OreError::DifficultyNotSatisfied,
+    OreError::BusRewardsInsufficient,
+    OreError::ClaimTooLarge,
+];
+
 impl Miner {
     pub async fn send_and_confirm(
         &self,
@@ -30,9 +57,24 @@ impl Miner {
         skip_confirm: bool,
     ) -> ClientResult<Signature> {
         let signer = self.signer();
+        let transactions = &self.session_stats.transactions;
+
+        // Request a larger heap, if configured
+        let mut heap_ixs = vec![];
+        if let Some(heap_bytes) = self.heap_bytes {
+            heap_ixs.push(ComputeBudgetInstruction::request_heap_frame(heap_bytes));
+        }
+        let ixs = &[heap_ixs, ixs.to_vec()].concat();
+
+        // Right-size the compute unit limit from earlier measurements (--cu-margin)
+        let cu_kind = ore_instruction_kind(ixs);
+        let ixs = &match self.margined_cu_limit(cu_kind) {
+            Some(cu_limit) => resize_cu_limit(ixs, cu_limit),
+            None => ixs.to_vec(),
+        };
 
         // Build tx
-        let (mut hash, mut slot) = self.get_latest_blockhash();
+        let (mut hash, mut slot) = self.get_latest_blockhash().await?;
         // let (mut hash, mut slot) = self
         //     .rpc_client
         //     .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
@@ -42,10 +84,12 @@ impl Miner {
             skip_preflight: true,
             preflight_commitment: Some(CommitmentLevel::Confirmed),
             encoding: Some(UiTransactionEncoding::Base64),
-            max_retries: Some(RPC_RETRIES),
+            max_retries: self.rpc_send_retries,
             min_context_slot: Some(slot),
         };
-        let mut tx = Transaction::new_with_payer(ixs, Some(&signer.pubkey()));
+        let mut tx_ixs = ixs.to_vec();
+        let mut tx =
+            Transaction::new_with_payer(&self.resolve_fee_unit(&tx_ixs), Some(&signer.pubkey()));
 
         // Simulate if necessary
         if dynamic_cus {
@@ -69,25 +113,37 @@ impl Miner {
                 match sim_res {
                     Ok(sim_res) => {
                         if let Some(err) = sim_res.value.err {
-                            println!("Simulaton error: {:?}", err);
+                            let err = describe_transaction_error(&tx, &err);
+                            println!("Simulaton error: {}", err);
                             sim_attempts += 1;
                             if sim_attempts.gt(&SIMULATION_RETRIES) {
                                 return Err(ClientError {
                                     request: None,
-                                    kind: ClientErrorKind::Custom("Simulation failed".into()),
+                                    kind: ClientErrorKind::Custom(format!(
+                                        "Simulation failed after {} attempts: {}\nLogs: {:#?}",
+                                        sim_attempts,
+                                        err,
+                                        sim_res.value.logs.unwrap_or_default()
+                                    )),
                                 });
                             }
                         } else if let Some(units_consumed) = sim_res.value.units_consumed {
                             println!("Dynamic CUs: {:?}", units_consumed);
+                            self.record_compute_units(cu_kind, units_consumed);
                             let cu_budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(
                                 units_consumed as u32 + 1000,
                             );
-                            let cu_price_ix =
-                                ComputeBudgetInstruction::set_compute_unit_price(self.priority_fee);
+                            let cu_price_ix = ComputeBudgetInstruction::set_compute_unit_price(
+                                self.get_priority_fee().await,
+                            );
                             let mut final_ixs = vec![];
                             final_ixs.extend_from_slice(&[cu_budget_ix, cu_price_ix]);
                             final_ixs.extend_from_slice(ixs);
-                            tx = Transaction::new_with_payer(&final_ixs, Some(&signer.pubkey()));
+                            tx = Transaction::new_with_payer(
+                                &self.resolve_fee_unit(&final_ixs),
+                                Some(&signer.pubkey()),
+                            );
+                            tx_ixs = final_ixs;
                             break 'simulate;
                         }
                     }
@@ -97,7 +153,10 @@ impl Miner {
                         if sim_attempts.gt(&SIMULATION_RETRIES) {
                             return Err(ClientError {
                                 request: None,
-                                kind: ClientErrorKind::Custom("Simulation failed".into()),
+                                kind: ClientErrorKind::Custom(format!(
+                                    "Simulation failed after {} attempts: {}",
+                                    sim_attempts, err
+                                )),
                             });
                         }
                     }
@@ -107,17 +166,70 @@ impl Miner {
 
         // Submit tx
         tx.sign(&[&signer], hash);
+        if self.sign_only {
+            // The transaction has not landed, so nothing after it can proceed
+            if let Err(err) = self.write_signed_transaction(&tx) {
+                return Err(ClientError {
+                    request: None,
+                    kind: ClientErrorKind::Custom(format!(
+                        "Failed to write signed transaction: {}",
+                        err
+                    )),
+                });
+            }
+            eprintln!(
+                "Stopping after the first signed transaction; submit it and run again to continue"
+            );
+            std::process::exit(0);
+        }
+        // Fee bumps (--auto-priority-fee-bump-secs) count from the first submission, across attempts
+        let first_submitted_at = Instant::now();
+        let mut last_bump_at = first_submitted_at;
         let mut sigs = vec![];
+        let mut failed_sigs = HashSet::new();
         let mut attempts = 0;
+        let mut priority_fee_bump = 0u64;
+
+        // Program errors are more informative than transient RPC errors, so they take precedence
+        let mut last_program_err: Option<String> = None;
+        let mut last_err: Option<String> = None;
         loop {
             println!("Attempt: {:?}", attempts);
-            match self
-                .rpc_client
-                .send_transaction_with_config(&tx, send_cfg)
-                .await
-            {
+            let mut blockhash_expired = false;
+
+            // Check the signed transaction would succeed before paying for it
+            if self.simulate_before_submit {
+                if let Some(err) = self.simulate_signed_transaction(&tx, cu_kind).await {
+                    return Err(ClientError {
+                        request: None,
+                        kind: ClientErrorKind::Custom(format!(
+                            "Simulation failed, transaction not submitted: {}",
+                            err
+                        )),
+                    });
+                }
+            }
+
+            let res = match self.send_transaction_compressed(&tx, send_cfg).await {
+                Some(res) => res,
+                None => self.send_transaction(&tx, send_cfg).await,
+            };
+
+            // A resubmitted transaction that already landed is not a failure
+            let res = match res {
+                Err(err) if is_already_processed(&err) => {
+                    println!("Transaction already processed, checking its status");
+                    Ok(tx.signatures[0])
+                }
+                res => res,
+            };
+            match res {
                 Ok(sig) => {
-                    sigs.push(sig);
+                    // Resends and already-processed responses reuse the signature, so count it once
+                    if !sigs.contains(&sig) {
+                        sigs.push(sig);
+                        transactions.submitted.fetch_add(1, Ordering::Relaxed);
+                    }
                     println!("{:?}", sig);
 
                     // Confirm tx
@@ -125,23 +237,64 @@ impl Miner {
                         return Ok(sig);
                     }
                     for _ in 0..CONFIRM_RETRIES {
-                        std::thread::sleep(Duration::from_millis(CONFIRM_DELAY));
+                        match self.resubmit_interval_ms {
+                            Some(interval_ms) => {
+                                self.resubmit_until_next_poll(&tx, send_cfg, slot, interval_ms)
+                                    .await
+                            }
+                            None => std::thread::sleep(Duration::from_millis(
+                                self.confirm_poll_interval_ms,
+                            )),
+                        }
                         match self.rpc_client.get_signature_statuses(&sigs).await {
                             Ok(signature_statuses) => {
                                 println!("Confirms: {:?}", signature_statuses.value);
-                                for signature_status in signature_statuses.value {
+                                for (status_sig, signature_status) in
+                                    sigs.iter().zip(signature_statuses.value)
+                                {
                                     if let Some(signature_status) = signature_status.as_ref() {
-                                        if signature_status.confirmation_status.is_some() {
-                                            let current_commitment = signature_status
-                                                .confirmation_status
-                                                .as_ref()
-                                                .unwrap();
+                                        if let Some(err) = signature_status.err.as_ref() {
+                                            let err = describe_transaction_error(&tx, err);
+                                            println!("Transaction failed: {}", err);
+                                            last_program_err = Some(err);
+                                            failed_sigs.insert(*status_sig);
+                                            continue;
+                                        }
+                                        if let Some(current_commitment) =
+                                            signature_status.confirmation_status.as_ref()
+                                        {
                                             match current_commitment {
                                                 TransactionConfirmationStatus::Processed => {}
                                                 TransactionConfirmationStatus::Confirmed
                                                 | TransactionConfirmationStatus::Finalized => {
                                                     println!("Transaction landed!");
-                                                    return Ok(sig);
+                                                    if self.wait_for_finality
+                                                        && current_commitment.ne(
+                                                            &TransactionConfirmationStatus::Finalized,
+                                                        )
+                                                    {
+                                                        self.wait_for_finalized(
+                                                            *status_sig,
+                                                            signature_status.slot,
+                                                        )
+                                                        .await;
+                                                    }
+                                                    self.measure_landed_transaction(
+                                                        *status_sig,
+                                                        cu_kind,
+                                                    )
+                                                    .await;
+                                                    self.record_failed_fees(&failed_sigs).await;
+                                                    self.on_transaction_landed();
+                                                    transactions.record_outcomes(
+                                                        &sigs,
+                                                        Some(*status_sig),
+                                                        &failed_sigs,
+                                                    );
+                                                    self.events.emit(Event::TransactionLanded {
+                                                        signature: status_sig.to_string(),
+                                                    });
+                                                    return Ok(*status_sig);
                                                 }
                                             }
                                         } else {
@@ -154,21 +307,47 @@ impl Miner {
                             // Handle confirmation errors
                             Err(err) => {
                                 println!("Error: {:?}", err);
+                                last_err = Some(err.to_string());
                             }
                         }
+
+                        // Stop waiting once --auto-priority-fee-bump-secs has elapsed
+                        if self.is_bump_due(last_bump_at) {
+                            break;
+                        }
                     }
                     println!("Transaction did not land");
+                    self.on_transaction_failed();
+                    self.events.emit(Event::TransactionFailed {
+                        error: "Transaction did not land".into(),
+                    });
                 }
 
                 // Handle submit errors
                 Err(err) => {
                     println!("Error {:?}", err);
+                    blockhash_expired = is_blockhash_not_found(&err);
+                    let err = match err.get_transaction_error() {
+                        Some(tx_err) => describe_transaction_error(&tx, &tx_err),
+                        None => err.to_string(),
+                    };
+                    last_err = Some(err.clone());
+                    self.on_transaction_failed();
+                    self.events.emit(Event::TransactionFailed { error: err });
                 }
             }
 
-            // Retry
-            std::thread::sleep(Duration::from_millis(GATEWAY_DELAY));
-            (hash, slot) = self.get_latest_blockhash();
+            // Retry, right away with a fresh blockhash if it expired (--retry-on-blockhash-not-found)
+            if blockhash_expired && self.retry_on_blockhash_not_found {
+                println!("Blockhash expired, refreshing it and resubmitting");
+                transactions
+                    .blockhash_refreshes
+                    .fetch_add(1, Ordering::Relaxed);
+                (hash, slot) = self.refresh_latest_blockhash().await?;
+            } else {
+                std::thread::sleep(Duration::from_millis(GATEWAY_DELAY));
+                (hash, slot) = self.get_latest_blockhash().await?;
+            }
             // (hash, slot) = self
             //     .rpc_client
             //     .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
@@ -178,17 +357,365 @@ impl Miner {
                 skip_preflight: true,
                 preflight_commitment: Some(CommitmentLevel::Confirmed),
                 encoding: Some(UiTransactionEncoding::Base64),
-                max_retries: Some(RPC_RETRIES),
+                max_retries: self.rpc_send_retries,
                 min_context_slot: Some(slot),
             };
+            if self.is_bump_due(last_bump_at) {
+                priority_fee_bump =
+                    priority_fee_bump.saturating_add(self.auto_priority_fee_bump_amount);
+                last_bump_at = Instant::now();
+            }
+            if self.priority_fee_increase_on_fail.gt(&1.0) || priority_fee_bump.gt(&0) {
+                let mut priority_fee = self
+                    .get_priority_fee()
+                    .await
+                    .saturating_add(priority_fee_bump);
+                if let Some(cap) = self.max_priority_fee_cap {
+                    priority_fee = priority_fee.min(cap);
+                }
+                if priority_fee_bump.gt(&0) {
+                    println!(
+                        "Transaction unconfirmed after {}s, resubmitting with priority fee {} microlamports",
+                        first_submitted_at.elapsed().as_secs(),
+                        priority_fee
+                    );
+                }
+                tx_ixs = reprice_instructions(&tx_ixs, priority_fee);
+                tx = Transaction::new_with_payer(
+                    &self.resolve_fee_unit(&tx_ixs),
+                    Some(&signer.pubkey()),
+                );
+            }
             tx.sign(&[&signer], hash);
             attempts += 1;
             if attempts > GATEWAY_RETRIES {
+                self.record_failed_fees(&failed_sigs).await;
+                transactions.record_outcomes(&sigs, None, &failed_sigs);
+                let reason = last_program_err
+                    .or(last_err)
+                    .unwrap_or_else(|| "transaction did not land".into());
                 return Err(ClientError {
                     request: None,
-                    kind: ClientErrorKind::Custom("Max retries".into()),
+                    kind: ClientErrorKind::Custom(format!(
+                        "Max retries exceeded after {} attempts: {}",
+                        attempts, reason
+                    )),
                 });
             }
         }
     }
+
+    /// Sends a transaction to the RPC, or with `--broadcast-all` to it and every
+    /// `--broadcast-rpc` concurrently, returning the first successful response.
+    async fn send_transaction(
+        &self,
+        tx: &Transaction,
+        send_cfg: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        if self.broadcast_clients.is_empty() {
+            return self
+                .rpc_client
+                .send_transaction_with_config(tx, send_cfg)
+                .await;
+        }
+
+        // Each send runs as its own task, so slower RPCs still get the transaction
+        // after the first one responds
+        let mut sends: FuturesUnordered<_> = std::iter::once(&self.rpc_client)
+            .chain(self.broadcast_clients.iter())
+            .map(|rpc_client| {
+                let rpc_client = rpc_client.clone();
+                let tx = tx.clone();
+                tokio::spawn(
+                    async move { rpc_client.send_transaction_with_config(&tx, send_cfg).await },
+                )
+            })
+            .collect();
+        let mut last_err = None;
+        while let Some(res) = sends.next().await {
+            match res {
+                Ok(Ok(sig)) => return Ok(sig),
+                Ok(Err(err)) => last_err = Some(err),
+                Err(err) => {
+                    last_err = Some(ClientError {
+                        request: None,
+                        kind: ClientErrorKind::Custom(format!("Broadcast task failed: {}", err)),
+                    })
+                }
+            }
+        }
+        Err(last_err.unwrap())
+    }
+
+    /// Polls a confirmed transaction until it is finalized (`--wait-for-finality`).
+    async fn wait_for_finalized(&self, sig: Signature, slot: u64) {
+        println!("Waiting for finality...");
+        loop {
+            tokio::time::sleep(Duration::from_millis(FINALITY_POLL_DELAY)).await;
+            if let Ok(finalized_slot) = self
+                .rpc_client
+                .get_slot_with_commitment(CommitmentConfig::finalized())
+                .await
+            {
+                println!(
+                    "Finalized slot {} (transaction in slot {})",
+                    finalized_slot, slot
+                );
+            }
+            let Ok(statuses) = self.rpc_client.get_signature_statuses(&[sig]).await else {
+                continue;
+            };
+            let Some(status) = statuses.value[0].as_ref() else {
+                println!(
+                    "WARNING: Transaction is no longer found, it may have been dropped in a fork"
+                );
+                return;
+            };
+            if status
+                .confirmation_status
+                .as_ref()
+                .eq(&Some(&TransactionConfirmationStatus::Finalized))
+            {
+                println!("Transaction finalized!");
+                return;
+            }
+        }
+    }
+
+    /// Waits out one confirmation poll interval, resubmitting `tx` every `interval_ms` meanwhile.
+    /// Stops resubmitting once the transaction has a status or its blockhash has expired.
+    async fn resubmit_until_next_poll(
+        &self,
+        tx: &Transaction,
+        send_cfg: RpcSendTransactionConfig,
+        last_valid_block_height: u64,
+        interval_ms: u64,
+    ) {
+        let started_at = Instant::now();
+        let poll_interval = Duration::from_millis(self.confirm_poll_interval_ms);
+        let mut resubmitting = true;
+        while started_at.elapsed().lt(&poll_interval) {
+            let remaining = poll_interval.saturating_sub(started_at.elapsed());
+            tokio::time::sleep(remaining.min(Duration::from_millis(interval_ms))).await;
+            if !resubmitting || started_at.elapsed().ge(&poll_interval) {
+                continue;
+            }
+
+            // Don't resubmit a transaction that already landed or can no longer land
+            let landed = match self
+                .rpc_client
+                .get_signature_statuses(&[tx.signatures[0]])
+                .await
+            {
+                Ok(statuses) => statuses.value[0].is_some(),
+                Err(_) => false,
+            };
+            let expired = match self.rpc_client.get_block_height().await {
+                Ok(block_height) => block_height.gt(&last_valid_block_height),
+                Err(_) => false,
+            };
+            if landed || expired {
+                resubmitting = false;
+                continue;
+            }
+            if let Err(err) = self.send_transaction(tx, send_cfg).await {
+                if !is_already_processed(&err) {
+                    println!("Resubmit error: {:?}", err);
+                }
+            }
+        }
+    }
+
+    /// Simulates a signed transaction for `--simulate-before-submit`, returning a readable
+    /// description of the error if it would fail. RPC errors are not treated as failures.
+    async fn simulate_signed_transaction(
+        &self,
+        tx: &Transaction,
+        cu_kind: Option<u8>,
+    ) -> Option<String> {
+        let sim_res = self
+            .rpc_client
+            .simulate_transaction_with_config(
+                tx,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: false,
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    accounts: None,
+                    min_context_slot: None,
+                    inner_instructions: false,
+                },
+            )
+            .await;
+        match sim_res {
+            Ok(sim_res) => {
+                // Failed runs stop early, so their units would drag the limit down
+                let Some(err) = sim_res.value.err else {
+                    if let Some(units_consumed) = sim_res.value.units_consumed {
+                        self.record_compute_units(cu_kind, units_consumed);
+                    }
+                    return None;
+                };
+                println!(
+                    "Simulation logs: {:#?}",
+                    sim_res.value.logs.unwrap_or_default()
+                );
+                Some(describe_transaction_error(tx, &err))
+            }
+            Err(err) => {
+                println!(
+                    "Failed to simulate transaction, submitting anyway: {:?}",
+                    err
+                );
+                None
+            }
+        }
+    }
+
+    /// Returns the compute unit limit for an instruction type with `--cu-margin` applied to
+    /// its last measured consumption, or `None` to keep the static limit.
+    fn margined_cu_limit(&self, cu_kind: Option<u8>) -> Option<u32> {
+        let margin = self.cu_margin?;
+        let observed = *self.observed_cus.lock().unwrap().get(&cu_kind?)?;
+        let cu_limit = observed.saturating_mul(100 + margin) / 100;
+        println!(
+            "CU limit: {} ({} last consumed + {}%)",
+            cu_limit, observed, margin
+        );
+        Some(cu_limit.min(u32::MAX as u64) as u32)
+    }
+
+    /// Remembers the compute units consumed by an instruction type, for `--cu-margin`.
+    fn record_compute_units(&self, cu_kind: Option<u8>, units_consumed: u64) {
+        if let Some(cu_kind) = cu_kind {
+            self.observed_cus
+                .lock()
+                .unwrap()
+                .insert(cu_kind, units_consumed);
+        }
+    }
+
+    /// Reads the fee and compute units of a landed transaction from its metadata, adding the
+    /// fee to the session's fees paid and, with `--cu-margin`, recording the compute units.
+    async fn measure_landed_transaction(&self, sig: Signature, cu_kind: Option<u8>) {
+        let Some(meta) = self.get_transaction_meta(sig).await else {
+            return;
+        };
+        self.session_stats
+            .fees_paid
+            .fetch_add(meta.fee, Ordering::Relaxed);
+        if self.cu_margin.is_none() || cu_kind.is_none() {
+            return;
+        }
+        if let OptionSerializer::Some(units_consumed) = meta.compute_units_consumed {
+            self.record_compute_units(cu_kind, units_consumed);
+        }
+    }
+
+    /// Adds the fees charged for transactions that failed on chain to the session's fees paid.
+    async fn record_failed_fees(&self, sigs: &HashSet<Signature>) {
+        for sig in sigs {
+            if let Some(meta) = self.get_transaction_meta(*sig).await {
+                self.session_stats
+                    .fees_paid
+                    .fetch_add(meta.fee, Ordering::Relaxed);
+            }
+        }
+    }
+
+    async fn get_transaction_meta(&self, sig: Signature) -> Option<UiTransactionStatusMeta> {
+        match self
+            .rpc_client
+            .get_transaction_with_config(
+                &sig,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await
+        {
+            Ok(tx) => tx.transaction.meta,
+            Err(err) => {
+                println!("Failed to read transaction {}: {:?}", sig, err);
+                None
+            }
+        }
+    }
+
+    /// Returns whether a transaction has waited long enough since `since` (its first
+    /// submission or last bump) to be resubmitted with a bumped fee (`--auto-priority-fee-bump-secs`).
+    fn is_bump_due(&self, since: Instant) -> bool {
+        self.auto_priority_fee_bump_secs
+            .is_some_and(|secs| since.elapsed().as_secs().ge(&secs))
+    }
+
+    /// Warns if `--auto-priority-fee-bump-secs` is longer than `send_and_confirm` keeps
+    /// retrying, since the fee would then never be bumped.
+    pub fn warn_if_fee_bump_unreachable(&self) {
+        let Some(bump_secs) = self.auto_priority_fee_bump_secs else {
+            return;
+        };
+        let attempt_ms =
+            (CONFIRM_RETRIES as u64 * self.confirm_poll_interval_ms).saturating_add(GATEWAY_DELAY);
+        let window_secs = attempt_ms.saturating_mul(GATEWAY_RETRIES as u64) / 1000;
+        if bump_secs.ge(&window_secs) {
+            eprintln!(
+                "warning: --auto-priority-fee-bump-secs {} is longer than a transaction is retried (~{}s), so its fee will never be bumped",
+                bump_secs, window_secs
+            );
+        }
+    }
+}
+
+/// Translates the errors a transaction commonly fails simulation with into plain language.
+fn describe_transaction_error(tx: &Transaction, err: &TransactionError) -> String {
+    match err {
+        TransactionError::InsufficientFundsForFee => format!(
+            "the signer {} does not have enough SOL to pay the transaction fee",
+            fee_payer(tx)
+        ),
+        TransactionError::BlockhashNotFound => "the blockhash has expired, try again".into(),
+        TransactionError::InstructionError(_, InstructionError::InsufficientFunds) => format!(
+            "the signer {} does not have enough funds for this transaction",
+            fee_payer(tx)
+        ),
+        TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {
+            "an account has unexpected data. Is the proof registered and the keypair correct?"
+                .into()
+        }
+        TransactionError::InstructionError(index, InstructionError::Custom(code))
+            if tx.message.program_id(*index as usize).eq(&Some(&ore::ID)) =>
+        {
+            match ORE_ERRORS.iter().find(|e| (**e as u32).eq(code)) {
+                Some(e) => format!("{:?}: {}", e, e),
+                None => err.to_string(),
+            }
+        }
+        _ => err.to_string(),
+    }
+}
+
+/// Returns the display name of the transaction's fee payer, for error messages.
+fn fee_payer(tx: &Transaction) -> String {
+    match tx.message.account_keys.first() {
+        Some(pubkey) => format_pubkey(pubkey),
+        None => "unknown".into(),
+    }
+}
+
+fn is_blockhash_not_found(err: &ClientError) -> bool {
+    match err.get_transaction_error() {
+        Some(TransactionError::BlockhashNotFound) => true,
+        _ => err.to_string().contains("Blockhash not found"),
+    }
+}
+
+fn is_already_processed(err: &ClientError) -> bool {
+    match err.get_transaction_error() {
+        Some(TransactionError::AlreadyProcessed) => true,
+        _ => err.to_string().contains("already been processed"),
+    }
 }
@@ -0,0 +1,91 @@
+use serde::Serialize;
+use solana_sdk::signature::Signer;
+
+use crate::{
+    utils::{ore_token_pubkey, proof_pubkey, treasury_tokens_pubkey, OutputFormat},
+    Miner,
+};
+
+#[derive(Debug, Serialize)]
+struct Accounts {
+    miner: String,
+    proof: String,
+    ore_token_account: String,
+    busses: Vec<String>,
+    treasury: String,
+    treasury_token_account: String,
+    mint: String,
+    program: String,
+    token_program: String,
+}
+
+impl Miner {
+    pub fn show_accounts(&self, format: OutputFormat) {
+        let pubkey = self.signer().pubkey();
+        let accounts = Accounts {
+            miner: pubkey.to_string(),
+            proof: proof_pubkey(pubkey).to_string(),
+            ore_token_account: ore_token_pubkey(pubkey).to_string(),
+            busses: self
+                .addresses
+                .busses
+                .iter()
+                .map(|address| address.to_string())
+                .collect(),
+            treasury: self.addresses.treasury.to_string(),
+            treasury_token_account: treasury_tokens_pubkey(self.addresses.treasury).to_string(),
+            mint: ore::MINT_ADDRESS.to_string(),
+            program: ore::ID.to_string(),
+            token_program: spl_token::id().to_string(),
+        };
+        match format {
+            OutputFormat::Text => {
+                println!("Miner: {}", accounts.miner);
+                println!("Proof: {}", accounts.proof);
+                println!("ORE token account: {}", accounts.ore_token_account);
+                for (id, address) in accounts.busses.iter().enumerate() {
+                    println!("Bus {}: {}", id, address);
+                }
+                println!("Treasury: {}", accounts.treasury);
+                println!(
+                    "Treasury token account: {}",
+                    accounts.treasury_token_account
+                );
+                println!("Mint: {}", accounts.mint);
+                println!("Ore program: {}", accounts.program);
+                println!("Token program: {}", accounts.token_program);
+            }
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string(&accounts).unwrap());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accounts_json_shape() {
+        let accounts = Accounts {
+            miner: "miner".into(),
+            proof: "proof".into(),
+            ore_token_account: "ata".into(),
+            busses: vec!["bus0".into(), "bus1".into()],
+            treasury: "treasury".into(),
+            treasury_token_account: "treasury_ata".into(),
+            mint: "mint".into(),
+            program: "program".into(),
+            token_program: "token_program".into(),
+        };
+        assert_eq!(
+            serde_json::to_string(&accounts).unwrap(),
+            concat!(
+                r#"{"miner":"miner","proof":"proof","ore_token_account":"ata","busses":["bus0","bus1"],"#,
+                r#""treasury":"treasury","treasury_token_account":"treasury_ata","mint":"mint","#,
+                r#""program":"program","token_program":"token_program"}"#
+            )
+        );
+    }
+}
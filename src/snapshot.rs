@@ -0,0 +1,174 @@
+use ore::{
+    state::{Bus, Proof, Treasury},
+    utils::AccountDeserialize,
+};
+use serde::Serialize;
+
+use crate::{Miner, SnapshotArgs};
+
+/// The snapshot file written by `ore snapshot`. Fields are serialized in declaration order.
+#[derive(Debug, Serialize)]
+struct Snapshot {
+    slot: u64,
+    busses: Vec<BusSnapshot>,
+    treasury: Option<TreasurySnapshot>,
+    proofs: Vec<ProofSnapshot>,
+}
+
+#[derive(Debug, Serialize)]
+struct BusSnapshot {
+    address: String,
+    id: u64,
+    rewards: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct TreasurySnapshot {
+    address: String,
+    admin: String,
+    bump: u64,
+    difficulty: String,
+    last_reset_at: i64,
+    reward_rate: u64,
+    total_claimed_rewards: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct ProofSnapshot {
+    address: String,
+    authority: String,
+    claimable_rewards: u64,
+    hash: String,
+    total_hashes: u64,
+    total_rewards: u64,
+}
+
+impl Miner {
+    pub async fn snapshot(&self, args: SnapshotArgs) {
+        let slot = match self.rpc_client.get_slot().await {
+            Ok(slot) => slot,
+            Err(err) => {
+                println!("Failed to fetch slot: {:?}", err);
+                return;
+            }
+        };
+        let accounts = match self.rpc_client.get_program_accounts(&ore::ID).await {
+            Ok(accounts) => accounts,
+            Err(err) => {
+                println!("Failed to fetch program accounts: {:?}", err);
+                return;
+            }
+        };
+
+        // Sort accounts into sections by type
+        let mut busses = vec![];
+        let mut treasury = None;
+        let mut proofs = vec![];
+        for (address, account) in accounts.iter() {
+            if let Ok(bus) = Bus::try_from_bytes(&account.data) {
+                busses.push(BusSnapshot {
+                    address: address.to_string(),
+                    id: bus.id,
+                    rewards: bus.rewards,
+                });
+            } else if let Ok(t) = Treasury::try_from_bytes(&account.data) {
+                treasury = Some(TreasurySnapshot {
+                    address: address.to_string(),
+                    admin: t.admin.to_string(),
+                    bump: t.bump,
+                    difficulty: t.difficulty.to_string(),
+                    last_reset_at: t.last_reset_at,
+                    reward_rate: t.reward_rate,
+                    total_claimed_rewards: t.total_claimed_rewards,
+                });
+            } else if let Ok(proof) = Proof::try_from_bytes(&account.data) {
+                proofs.push(ProofSnapshot {
+                    address: address.to_string(),
+                    authority: proof.authority.to_string(),
+                    claimable_rewards: proof.claimable_rewards,
+                    hash: proof.hash.to_string(),
+                    total_hashes: proof.total_hashes,
+                    total_rewards: proof.total_rewards,
+                });
+            }
+        }
+
+        let snapshot = Snapshot {
+            slot,
+            busses,
+            treasury,
+            proofs,
+        };
+        if let Err(err) = std::fs::write(
+            &args.output,
+            serde_json::to_string_pretty(&snapshot).unwrap(),
+        ) {
+            println!("Failed to write {}: {:?}", args.output, err);
+            return;
+        }
+        println!(
+            "Wrote {} busses, {} treasury and {} proofs at slot {} to {}",
+            snapshot.busses.len(),
+            snapshot.treasury.iter().count(),
+            snapshot.proofs.len(),
+            slot,
+            args.output
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_json_shape() {
+        let snapshot = Snapshot {
+            slot: 7,
+            busses: vec![BusSnapshot {
+                address: "bus".into(),
+                id: 0,
+                rewards: 10,
+            }],
+            treasury: Some(TreasurySnapshot {
+                address: "treasury".into(),
+                admin: "admin".into(),
+                bump: 255,
+                difficulty: "difficulty".into(),
+                last_reset_at: 1,
+                reward_rate: 2,
+                total_claimed_rewards: 3,
+            }),
+            proofs: vec![ProofSnapshot {
+                address: "proof".into(),
+                authority: "authority".into(),
+                claimable_rewards: 4,
+                hash: "hash".into(),
+                total_hashes: 5,
+                total_rewards: 6,
+            }],
+        };
+        assert_eq!(
+            serde_json::to_string(&snapshot).unwrap(),
+            concat!(
+                r#"{"slot":7,"busses":[{"address":"bus","id":0,"rewards":10}],"#,
+                r#""treasury":{"address":"treasury","admin":"admin","bump":255,"difficulty":"difficulty","last_reset_at":1,"reward_rate":2,"total_claimed_rewards":3},"#,
+                r#""proofs":[{"address":"proof","authority":"authority","claimable_rewards":4,"hash":"hash","total_hashes":5,"total_rewards":6}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn snapshot_json_without_treasury() {
+        let snapshot = Snapshot {
+            slot: 0,
+            busses: vec![],
+            treasury: None,
+            proofs: vec![],
+        };
+        assert_eq!(
+            serde_json::to_string(&snapshot).unwrap(),
+            r#"{"slot":0,"busses":[],"treasury":null,"proofs":[]}"#
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use std::path::{Path, PathBuf};
+
+use futures::{stream::FuturesUnordered, StreamExt};
+use ore::state::Proof;
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Signer};
+
+use crate::{
+    utils::{format_pubkey, parse_account, proof_pubkey, resolve_ata},
+    Miner, SnapshotBalanceArgs,
+};
+
+#[derive(Debug, Serialize)]
+struct BalanceSnapshot {
+    pubkey: String,
+    timestamp: i64,
+    slot: u64,
+    balance: u64,
+    claimable_rewards: u64,
+}
+
+impl Miner {
+    pub async fn snapshot_balance(&self, args: SnapshotBalanceArgs) {
+        // Collect the addresses to snapshot
+        let addresses = match args.keypairs_dir.as_ref() {
+            Some(keypairs_dir) => match read_keypairs_dir(keypairs_dir) {
+                Ok(addresses) => addresses,
+                Err(err) => {
+                    println!("Failed to read keypairs dir {}: {:?}", keypairs_dir, err);
+                    return;
+                }
+            },
+            None => match self.default_address() {
+                Some(address) => vec![address],
+                None => return,
+            },
+        };
+
+        if let Err(err) = std::fs::create_dir_all(&args.output_dir) {
+            println!("Failed to create output dir {}: {:?}", args.output_dir, err);
+            return;
+        }
+
+        // Snapshot wallets in parallel, bounded by --max-concurrency
+        let output_dir = args.output_dir.as_str();
+        let mut futures: FuturesUnordered<_> = addresses
+            .into_iter()
+            .map(|address| async move {
+                let _permit = self.wallet_permits.acquire().await.unwrap();
+                (
+                    address,
+                    self.write_balance_snapshot(address, output_dir).await,
+                )
+            })
+            .collect();
+        while let Some((address, res)) = futures.next().await {
+            match res {
+                Ok(path) => println!("{}", path.display()),
+                Err(err) => println!("Failed to snapshot {}: {:?}", format_pubkey(&address), err),
+            }
+        }
+    }
+
+    async fn write_balance_snapshot(
+        &self,
+        address: Pubkey,
+        output_dir: &str,
+    ) -> anyhow::Result<PathBuf> {
+        let slot = self.rpc_client.get_slot().await?;
+        let balance = match resolve_ata(&self.rpc_client, address).await? {
+            Some(token_account) => token_account.token_amount.amount.parse()?,
+            None => 0,
+        };
+        // Only a missing proof means nothing is claimable; other errors fail the snapshot
+        let claimable_rewards = match self
+            .rpc_client
+            .get_account_with_commitment(&proof_pubkey(address), self.rpc_client.commitment())
+            .await?
+            .value
+        {
+            Some(account) => {
+                parse_account::<Proof>(&account.data)
+                    .map_err(anyhow::Error::msg)?
+                    .claimable_rewards
+            }
+            None => 0,
+        };
+
+        let snapshot = BalanceSnapshot {
+            pubkey: address.to_string(),
+            timestamp: chrono::Utc::now().timestamp(),
+            slot,
+            balance,
+            claimable_rewards,
+        };
+        let path = Path::new(output_dir).join(format!("{}_{}.json", address, snapshot.timestamp));
+        std::fs::write(&path, serde_json::to_string_pretty(&snapshot)?)?;
+        Ok(path)
+    }
+}
+
+fn read_keypairs_dir(keypairs_dir: &str) -> std::io::Result<Vec<Pubkey>> {
+    let mut addresses = vec![];
+    for entry in std::fs::read_dir(keypairs_dir)?.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext.eq("json")) {
+            match read_keypair_file(&path) {
+                Ok(keypair) => addresses.push(keypair.pubkey()),
+                Err(err) => println!("Skipping {}: {}", path.display(), err),
+            }
+        }
+    }
+    Ok(addresses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balance_snapshot_json_shape() {
+        let snapshot = BalanceSnapshot {
+            pubkey: "pubkey".into(),
+            timestamp: 1,
+            slot: 2,
+            balance: 3,
+            claimable_rewards: 4,
+        };
+        assert_eq!(
+            serde_json::to_string(&snapshot).unwrap(),
+            r#"{"pubkey":"pubkey","timestamp":1,"slot":2,"balance":3,"claimable_rewards":4}"#
+        );
+    }
+}
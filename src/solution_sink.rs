@@ -0,0 +1,86 @@
+use std::{fs::File, io::Write, str::FromStr, sync::Mutex};
+
+use serde::Serialize;
+
+/// Where `--solution-sink` sends solutions: an HTTP(S) endpoint, or an inherited
+/// file descriptor given as `fd:N`.
+#[derive(Clone, Debug)]
+pub enum SolutionSinkTarget {
+    Http(String),
+    Fd(i32),
+}
+
+impl FromStr for SolutionSinkTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("http://") || s.starts_with("https://") {
+            return Ok(Self::Http(s.to_string()));
+        }
+        match s.strip_prefix("fd:") {
+            Some(fd) if cfg!(unix) => fd
+                .parse::<i32>()
+                .ok()
+                .filter(|fd| fd.ge(&0))
+                .map(Self::Fd)
+                .ok_or_else(|| format!("invalid file descriptor: {}", fd)),
+            Some(_) => Err("fd: sinks are only supported on unix".into()),
+            None => Err(format!("expected an http(s):// URL or fd:N, got \"{}\"", s)),
+        }
+    }
+}
+
+/// A mining solution, as handed to an external submitter.
+#[derive(Debug, Serialize)]
+pub struct Solution {
+    pub authority: String,
+    pub proof: String,
+    pub challenge: String,
+    pub hash: String,
+    pub nonce: u64,
+}
+
+pub enum SolutionSink {
+    Http(reqwest::Client, String),
+    File(Mutex<File>),
+}
+
+impl SolutionSink {
+    pub fn open(target: &SolutionSinkTarget, http_client: &reqwest::Client) -> Self {
+        match target {
+            SolutionSinkTarget::Http(url) => Self::Http(http_client.clone(), url.clone()),
+            #[cfg(unix)]
+            SolutionSinkTarget::Fd(fd) => {
+                use std::os::unix::io::FromRawFd;
+                // The descriptor is inherited from the parent process and owned by the sink from here on
+                Self::File(Mutex::new(unsafe { File::from_raw_fd(*fd) }))
+            }
+            #[cfg(not(unix))]
+            SolutionSinkTarget::Fd(_) => unreachable!("fd: sinks are rejected when parsing"),
+        }
+    }
+
+    /// Posts the solution as JSON, or writes it as a line of JSON to the file descriptor.
+    pub async fn send(&self, solution: &Solution) {
+        match self {
+            Self::Http(http_client, url) => match http_client.post(url).json(solution).send().await
+            {
+                Ok(res) if !res.status().is_success() => {
+                    println!("Solution sink returned status {}", res.status());
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    println!("Failed to send solution to sink: {:?}", err);
+                }
+            },
+            Self::File(file) => {
+                let mut line = serde_json::to_string(solution).unwrap();
+                line.push('\n');
+                let mut file = file.lock().unwrap();
+                if let Err(err) = file.write_all(line.as_bytes()).and_then(|_| file.flush()) {
+                    println!("Failed to write solution to sink: {:?}", err);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+use crate::{
+    events::{Event, EventSink},
+    metrics::{write_influxdb, InfluxDbConfig},
+    utils::format_pubkey,
+    webhook::notify_webhook,
+};
+
+/// Counters accumulated over a mining session.
+pub struct SessionStats {
+    pub started_at: Instant,
+    pub hashes: AtomicU64,
+    pub rounds: AtomicU64,
+    pub ore_earned: AtomicU64,
+    pub fees_paid: AtomicU64,
+    pub peak_hash_rate: AtomicU64,
+    pub transactions: TransactionStats,
+}
+
+/// Outcomes of the transactions submitted by `send_and_confirm`. Each unique signature
+/// is counted once in `submitted` and at most once in `confirmed`, `failed` or `timed_out`.
+#[derive(Default)]
+pub struct TransactionStats {
+    pub submitted: AtomicU64,
+    pub confirmed: AtomicU64,
+    pub failed: AtomicU64,
+    pub timed_out: AtomicU64,
+    pub blockhash_refreshes: AtomicU64,
+}
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+/// A point-in-time snapshot of the session stats, as printed in periodic reports.
+#[derive(Debug, Serialize)]
+pub struct MiningReport {
+    pub timestamp: i64,
+    pub elapsed_secs: u64,
+    pub rounds: u64,
+    pub hashes: u64,
+    pub hash_rate: f64,
+    pub ore_earned: f64,
+    pub ore_per_hour: f64,
+    pub landing_rate: Option<f64>,
+    pub blockhash_refreshes: u64,
+}
+
+/// The outcome of a mining session, printed as the last line of JSON output when mining stops.
+#[derive(Debug, Serialize)]
+pub struct SessionSummary {
+    pub rounds: u64,
+    pub ore_earned: f64,
+    pub fees_lamports: u64,
+    pub avg_round_secs: Option<f64>,
+    pub peak_hashrate: u64,
+}
+
+/// A found solution, as logged with `--solution-log-format`.
+#[derive(Debug, Serialize)]
+pub struct SolutionLog {
+    pub nonce: u64,
+    pub hash: String,
+    pub leading_zeros: u32,
+    pub reward_estimate: u64,
+    pub timestamp: String,
+}
+
+/// Settings for the periodic report task.
+pub struct ReportConfig {
+    pub interval_secs: u64,
+    pub format: ReportFormat,
+    pub expected_rewards_per_hour: Option<f64>,
+    pub reward_alert_threshold: f64,
+    pub min_landing_rate: Option<f64>,
+    pub notify_webhook: Option<String>,
+    pub http_client: reqwest::Client,
+    pub influxdb: Option<InfluxDbConfig>,
+    pub pubkey: Pubkey,
+    pub tag: String,
+    pub log_prefix: String,
+}
+
+impl SessionStats {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            hashes: AtomicU64::new(0),
+            rounds: AtomicU64::new(0),
+            ore_earned: AtomicU64::new(0),
+            fees_paid: AtomicU64::new(0),
+            peak_hash_rate: AtomicU64::new(0),
+            transactions: TransactionStats::default(),
+        }
+    }
+
+    pub fn report(&self) -> MiningReport {
+        let elapsed = self.started_at.elapsed();
+        let hashes = self.hashes.load(Ordering::Relaxed);
+        let ore_earned = (self.ore_earned.load(Ordering::Relaxed) as f64)
+            / 10f64.powf(ore::TOKEN_DECIMALS as f64);
+        MiningReport {
+            timestamp: chrono::Utc::now().timestamp(),
+            elapsed_secs: elapsed.as_secs(),
+            rounds: self.rounds.load(Ordering::Relaxed),
+            hashes,
+            hash_rate: hashes as f64 / elapsed.as_secs_f64().max(1.0),
+            ore_earned,
+            ore_per_hour: ore_earned / (elapsed.as_secs_f64().max(1.0) / 3600.0),
+            landing_rate: self.transactions.landing_rate(),
+            blockhash_refreshes: self
+                .transactions
+                .blockhash_refreshes
+                .load(Ordering::Relaxed),
+        }
+    }
+
+    /// Records the hash rate of a round, keeping the highest seen.
+    pub fn record_hash_rate(&self, hashes: u64, elapsed: Duration) {
+        let hash_rate = (hashes as f64 / elapsed.as_secs_f64().max(0.001)) as u64;
+        self.peak_hash_rate.fetch_max(hash_rate, Ordering::Relaxed);
+    }
+
+    pub fn summary(&self) -> SessionSummary {
+        let rounds = self.rounds.load(Ordering::Relaxed);
+        SessionSummary {
+            rounds,
+            ore_earned: (self.ore_earned.load(Ordering::Relaxed) as f64)
+                / 10f64.powf(ore::TOKEN_DECIMALS as f64),
+            fees_lamports: self.fees_paid.load(Ordering::Relaxed),
+            avg_round_secs: (rounds > 0)
+                .then(|| self.started_at.elapsed().as_secs_f64() / rounds as f64),
+            peak_hashrate: self.peak_hash_rate.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl TransactionStats {
+    /// Records the outcome of every signature sent for one transaction: `landed` is confirmed,
+    /// the others failed if they errored on chain and timed out otherwise.
+    pub fn record_outcomes(
+        &self,
+        sigs: &[Signature],
+        landed: Option<Signature>,
+        failed: &HashSet<Signature>,
+    ) {
+        for sig in sigs {
+            let counter = if landed.eq(&Some(*sig)) {
+                &self.confirmed
+            } else if failed.contains(sig) {
+                &self.failed
+            } else {
+                &self.timed_out
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the fraction of submitted transactions that were confirmed,
+    /// or `None` if nothing has been submitted yet.
+    pub fn landing_rate(&self) -> Option<f64> {
+        let submitted = self.submitted.load(Ordering::Relaxed);
+        if submitted == 0 {
+            return None;
+        }
+        Some(self.confirmed.load(Ordering::Relaxed) as f64 / submitted as f64)
+    }
+}
+
+impl MiningReport {
+    pub const CSV_HEADER: &'static str =
+        "timestamp,elapsed_secs,rounds,hashes,hash_rate,ore_earned,ore_per_hour,landing_rate,blockhash_refreshes";
+
+    pub fn print(&self, format: ReportFormat, prefix: &str, first: bool) {
+        match format {
+            ReportFormat::Text => {
+                let landing_rate = match self.landing_rate {
+                    Some(landing_rate) => format!("{:.0}%", landing_rate * 100.0),
+                    None => "n/a".into(),
+                };
+                println!(
+                    "{}Report: {}s elapsed, {} rounds, {} hashes ({:.0} H/s), {} ORE earned ({:.4} ORE/h), {} of txs landed, {} blockhash refreshes",
+                    prefix,
+                    self.elapsed_secs,
+                    self.rounds,
+                    self.hashes,
+                    self.hash_rate,
+                    self.ore_earned,
+                    self.ore_per_hour,
+                    landing_rate,
+                    self.blockhash_refreshes
+                );
+            }
+            ReportFormat::Json => {
+                println!("{}", serde_json::to_string(self).unwrap());
+            }
+            ReportFormat::Csv => {
+                if first {
+                    println!("{}", Self::CSV_HEADER);
+                }
+                println!(
+                    "{},{},{},{},{:.2},{},{:.9},{},{}",
+                    self.timestamp,
+                    self.elapsed_secs,
+                    self.rounds,
+                    self.hashes,
+                    self.hash_rate,
+                    self.ore_earned,
+                    self.ore_per_hour,
+                    self.landing_rate
+                        .map(|landing_rate| format!("{:.4}", landing_rate))
+                        .unwrap_or_default(),
+                    self.blockhash_refreshes
+                );
+            }
+        }
+    }
+}
+
+impl SolutionLog {
+    pub const CSV_HEADER: &'static str = "nonce,hash,leading_zeros,reward_estimate,timestamp";
+
+    pub fn print(&self, format: ReportFormat, prefix: &str, first: bool) {
+        match format {
+            ReportFormat::Text => {
+                println!(
+                    "{}found solution: hash={}, nonce={}",
+                    prefix, self.hash, self.nonce
+                );
+            }
+            ReportFormat::Json => {
+                println!("{}", serde_json::to_string(self).unwrap());
+            }
+            ReportFormat::Csv => {
+                if first {
+                    println!("{}", Self::CSV_HEADER);
+                }
+                println!(
+                    "{},{},{},{},{}",
+                    self.nonce, self.hash, self.leading_zeros, self.reward_estimate, self.timestamp
+                );
+            }
+        }
+    }
+}
+
+impl SessionSummary {
+    pub fn print(&self) {
+        println!("{}", serde_json::to_string(self).unwrap());
+    }
+}
+
+pub async fn report_session_stats(
+    stats: Arc<SessionStats>,
+    events: Arc<EventSink>,
+    config: ReportConfig,
+) -> ! {
+    let mut first = true;
+    loop {
+        tokio::time::sleep(Duration::from_secs(config.interval_secs)).await;
+        let report = stats.report();
+        report.print(config.format, &config.log_prefix, first);
+
+        // Alert if rewards fall behind expectations
+        if let Some(expected) = config.expected_rewards_per_hour {
+            let minimum = expected * config.reward_alert_threshold;
+            if report.ore_per_hour.lt(&minimum) {
+                let message = format!(
+                    "Rewards below expectation: {:.4} ORE/h (expected {:.4} ORE/h, alert below {:.4} ORE/h)",
+                    report.ore_per_hour, expected, minimum
+                );
+                println!("{}WARNING: {}", config.log_prefix, message);
+                if let Some(url) = config.notify_webhook.as_ref() {
+                    let message = format!(
+                        "[{}] {}: {}",
+                        config.tag,
+                        format_pubkey(&config.pubkey),
+                        message
+                    );
+                    notify_webhook(&config.http_client, url, &message).await;
+                }
+            }
+        }
+
+        // Alert if too few transactions land
+        if let (Some(minimum), Some(landing_rate)) = (config.min_landing_rate, report.landing_rate)
+        {
+            if landing_rate.lt(&minimum) {
+                let message = format!(
+                    "Transaction landing rate below minimum: {:.0}% (alert below {:.0}%)",
+                    landing_rate * 100.0,
+                    minimum * 100.0
+                );
+                println!("{}WARNING: {}", config.log_prefix, message);
+                if let Some(url) = config.notify_webhook.as_ref() {
+                    let message = format!(
+                        "[{}] {}: {}",
+                        config.tag,
+                        format_pubkey(&config.pubkey),
+                        message
+                    );
+                    notify_webhook(&config.http_client, url, &message).await;
+                }
+            }
+        }
+
+        if let Some(influxdb) = config.influxdb.as_ref() {
+            let fees_paid = stats.fees_paid.load(Ordering::Relaxed);
+            write_influxdb(
+                &config.http_client,
+                influxdb,
+                &config.pubkey,
+                &config.tag,
+                &report,
+                fees_paid,
+            )
+            .await;
+        }
+
+        events.emit(Event::Report(report));
+        first = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_one_outcome_per_signature() {
+        let stats = TransactionStats::default();
+        let (landed, failed, timed_out) = (
+            Signature::new_unique(),
+            Signature::new_unique(),
+            Signature::new_unique(),
+        );
+        stats.submitted.fetch_add(3, Ordering::Relaxed);
+        stats.record_outcomes(
+            &[failed, timed_out, landed],
+            Some(landed),
+            &HashSet::from([failed]),
+        );
+        assert_eq!(stats.confirmed.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.failed.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.timed_out.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.landing_rate(), Some(1.0 / 3.0));
+    }
+
+    #[test]
+    fn mining_report_json_shape() {
+        let report = MiningReport {
+            timestamp: 1,
+            elapsed_secs: 2,
+            rounds: 3,
+            hashes: 4,
+            hash_rate: 2.0,
+            ore_earned: 0.5,
+            ore_per_hour: 1.25,
+            landing_rate: None,
+            blockhash_refreshes: 5,
+        };
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            concat!(
+                r#"{"timestamp":1,"elapsed_secs":2,"rounds":3,"hashes":4,"hash_rate":2.0,"#,
+                r#""ore_earned":0.5,"ore_per_hour":1.25,"landing_rate":null,"blockhash_refreshes":5}"#
+            )
+        );
+    }
+
+    #[test]
+    fn session_summary_json_shape() {
+        let summary = SessionSummary {
+            rounds: 1,
+            ore_earned: 0.25,
+            fees_lamports: 5000,
+            avg_round_secs: Some(30.0),
+            peak_hashrate: 100,
+        };
+        assert_eq!(
+            serde_json::to_string(&summary).unwrap(),
+            r#"{"rounds":1,"ore_earned":0.25,"fees_lamports":5000,"avg_round_secs":30.0,"peak_hashrate":100}"#
+        );
+    }
+
+    #[test]
+    fn solution_log_json_shape() {
+        let solution = SolutionLog {
+            nonce: 1,
+            hash: "hash".into(),
+            leading_zeros: 2,
+            reward_estimate: 3,
+            timestamp: "2024-01-01T00:00:00Z".into(),
+        };
+        assert_eq!(
+            serde_json::to_string(&solution).unwrap(),
+            r#"{"nonce":1,"hash":"hash","leading_zeros":2,"reward_estimate":3,"timestamp":"2024-01-01T00:00:00Z"}"#
+        );
+    }
+}
@@ -1,20 +1,20 @@
 use crate::{
-    utils::{get_treasury, treasury_tokens_pubkey},
-    Miner,
+    utils::{get_treasury, print_raw_account, treasury_tokens_pubkey},
+    Miner, TreasuryArgs,
 };
 
 impl Miner {
-    pub async fn treasury(&self) {
+    pub async fn treasury(&self, args: TreasuryArgs) {
         if let Ok(Some(treasury_tokens)) = self
             .rpc_client
-            .get_token_account(&treasury_tokens_pubkey())
+            .get_token_account(&treasury_tokens_pubkey(self.addresses.treasury))
             .await
         {
-            let treasury = get_treasury(&self.rpc_client).await;
+            let treasury = get_treasury(&self.rpc_client, &self.addresses.treasury).await;
             let balance = treasury_tokens.token_amount.ui_amount_string;
             println!("{:} ORE", balance);
             println!("Admin: {}", treasury.admin);
-            println!("Difficulty: {}", treasury.difficulty.to_string());
+            println!("Difficulty: {}", treasury.difficulty);
             println!("Last reset at: {}", treasury.last_reset_at);
             println!(
                 "Reward rate: {} ORE",
@@ -24,6 +24,16 @@ impl Miner {
                 "Total claimed rewards: {} ORE",
                 (treasury.total_claimed_rewards as f64) / 10f64.powf(ore::TOKEN_DECIMALS as f64)
             );
+            if args.raw_account {
+                match self
+                    .rpc_client
+                    .get_account_data(&self.addresses.treasury)
+                    .await
+                {
+                    Ok(data) => print_raw_account(&data),
+                    Err(err) => println!("Failed to fetch treasury account: {:?}", err),
+                }
+            }
         }
     }
 }
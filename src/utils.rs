@@ -1,35 +1,228 @@
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex, RwLock},
+    time::Duration,
+};
+
+use base64::Engine;
 use cached::proc_macro::cached;
+use clap::ValueEnum;
 use ore::{
     self,
     state::{Proof, Treasury},
     utils::AccountDeserialize,
-    MINT_ADDRESS, PROOF, TREASURY_ADDRESS,
+    BUS_ADDRESSES, BUS_COUNT, MINT_ADDRESS, PROOF, TREASURY_ADDRESS,
 };
-use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_account_decoder::parse_token::UiTokenAccount;
+use solana_client::{client_error::Result as ClientResult, nonblocking::rpc_client::RpcClient};
 use solana_program::{pubkey::Pubkey, sysvar};
-use solana_sdk::clock::Clock;
+use solana_sdk::{clock::Clock, commitment_config::CommitmentConfig};
 use spl_associated_token_account::get_associated_token_address;
 
-pub async fn get_treasury(rpc_client: &RpcClient) -> Treasury {
-    let data = rpc_client
-        .get_account_data(&TREASURY_ADDRESS)
+// Retries and delay for read-only RPC calls
+const READ_RETRIES: usize = 3;
+const READ_RETRY_DELAY: u64 = 500;
+
+// Decimal places of a SOL amount, down to lamports
+const SOL_DECIMALS: usize = 9;
+
+// Timeout of a whole HTTP request outside the RPC client (fee oracle, webhooks, sinks, metrics)
+const HTTP_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Output format of commands that print account state.
+/// JSON output is always serialized from a per-command struct, so keys appear in field declaration order
+/// and amounts are integers in base units unless the field name says otherwise.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Parses a `YYYY-MM-DD` date (midnight UTC) or an RFC 3339 time into a unix timestamp.
+pub fn parse_timestamp(s: &str) -> Result<i64, String> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(date.and_time(chrono::NaiveTime::MIN).and_utc().timestamp());
+    }
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|time| time.timestamp())
+        .map_err(|_| format!("expected a YYYY-MM-DD date or RFC 3339 time, got {:?}", s))
+}
+
+/// Parses a human-friendly ORE amount such as `1.5`, `1_000` or `2 ORE` into base units.
+/// Amounts with more fractional digits than the token has decimals are rejected rather than truncated.
+pub fn parse_ore_amount(s: &str) -> Result<u64, String> {
+    parse_decimal_amount(s, "ORE", ore::TOKEN_DECIMALS as usize)
+}
+
+/// Parses a SOL amount such as `0.5` or `1 SOL` into lamports, rejecting zero.
+pub fn parse_sol_amount(s: &str) -> Result<u64, String> {
+    match parse_decimal_amount(s, "SOL", SOL_DECIMALS)? {
+        0 => Err("SOL amount must be greater than zero".into()),
+        amount => Ok(amount),
+    }
+}
+
+/// Parses an exact decimal amount of a token with `decimals` decimal places into base units,
+/// with an optional `symbol` suffix.
+fn parse_decimal_amount(s: &str, symbol: &str, decimals: usize) -> Result<u64, String> {
+    let s = s.trim();
+    let s = s
+        .strip_suffix(symbol)
+        .or_else(|| s.strip_suffix(symbol.to_lowercase().as_str()))
+        .unwrap_or(s)
+        .trim_end()
+        .replace('_', "");
+    let (whole, frac) = s.split_once('.').unwrap_or((&s, ""));
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && frac.is_empty()) || !is_digits(whole) || !is_digits(frac) {
+        return Err(format!("invalid {} amount `{}`", symbol, s));
+    }
+    if frac.len() > decimals {
+        return Err(format!(
+            "{} amounts have at most {} decimal places, got {}",
+            symbol,
+            decimals,
+            frac.len()
+        ));
+    }
+    let too_large = || format!("{} amount is too large", symbol);
+    let whole: u64 = match whole {
+        "" => 0,
+        whole => whole.parse().map_err(|_| too_large())?,
+    };
+    let frac: u64 = format!("{:0<width$}", frac, width = decimals)
+        .parse()
+        .map_err(|_| format!("invalid {} amount `{}`", symbol, s))?;
+    whole
+        .checked_mul(10u64.pow(decimals as u32))
+        .and_then(|whole| whole.checked_add(frac))
+        .ok_or_else(too_large)
+}
+
+/// Parses an ORE amount like [`parse_ore_amount`], rejecting zero.
+pub fn parse_nonzero_ore_amount(s: &str) -> Result<u64, String> {
+    match parse_ore_amount(s)? {
+        0 => Err("ORE amount must be greater than zero".into()),
+        amount => Ok(amount),
+    }
+}
+
+/// Creates the HTTP client shared by requests made outside the RPC client.
+/// Connecting gives up after `connect_timeout_ms` (--connect-timeout-ms).
+pub fn new_http_client(connect_timeout_ms: u64) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(HTTP_REQUEST_TIMEOUT)
+        .connect_timeout(Duration::from_millis(connect_timeout_ms))
+        .build()
+        .expect("build http client")
+}
+
+/// Retries a read-only RPC call on errors, so a transient failure doesn't abort the command.
+pub async fn with_retries<T, F, Fut>(f: F) -> ClientResult<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = ClientResult<T>>,
+{
+    let mut attempts = 0;
+    loop {
+        match f().await {
+            Ok(res) => return Ok(res),
+            Err(err) => {
+                attempts += 1;
+                if attempts.gt(&READ_RETRIES) {
+                    return Err(err);
+                }
+                println!("RPC read failed, retrying: {}", err);
+                tokio::time::sleep(Duration::from_millis(READ_RETRY_DELAY)).await;
+            }
+        }
+    }
+}
+
+/// Addresses of the ore accounts this CLI reads, which differ on custom deployments.
+#[derive(Clone, Copy, Debug)]
+pub struct ProgramAddresses {
+    pub busses: [Pubkey; BUS_COUNT],
+    pub treasury: Pubkey,
+}
+
+impl Default for ProgramAddresses {
+    fn default() -> Self {
+        Self {
+            busses: BUS_ADDRESSES,
+            treasury: TREASURY_ADDRESS,
+        }
+    }
+}
+
+pub async fn get_treasury(rpc_client: &RpcClient, address: &Pubkey) -> Treasury {
+    let data = with_retries(|| rpc_client.get_account_data(address))
         .await
         .expect("Failed to get treasury account");
     *Treasury::try_from_bytes(&data).expect("Failed to parse treasury account")
 }
 
+/// Parses ore account data, checking its length first so empty or truncated
+/// accounts return an error instead of panicking inside `try_from_bytes`.
+pub fn parse_account<T: AccountDeserialize + Copy>(data: &[u8]) -> Result<T, String> {
+    let expected = 8 + std::mem::size_of::<T>();
+    if data.is_empty() {
+        return Err("account data is empty".into());
+    }
+    if data.len() < expected {
+        return Err(format!(
+            "account data is {} bytes, expected {}",
+            data.len(),
+            expected
+        ));
+    }
+    T::try_from_bytes(data)
+        .copied()
+        .map_err(|err| err.to_string())
+}
+
+/// Prints raw account data as base64 and hex, for `--raw-account`.
+pub fn print_raw_account(data: &[u8]) {
+    println!(
+        "Raw (base64): {}",
+        base64::engine::general_purpose::STANDARD.encode(data)
+    );
+    println!(
+        "Raw (hex): {}",
+        data.iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    );
+}
+
 pub async fn get_proof(rpc_client: &RpcClient, authority: Pubkey) -> Proof {
     let proof_address = proof_pubkey(authority);
-    let data = rpc_client
-        .get_account_data(&proof_address)
+    let data = with_retries(|| rpc_client.get_account_data(&proof_address))
         .await
         .expect("Failed to get miner account");
     *Proof::try_from_bytes(&data).expect("Failed to parse miner account")
 }
 
+pub async fn get_proof_with_commitment(
+    rpc_client: &RpcClient,
+    authority: Pubkey,
+    commitment: CommitmentConfig,
+) -> Proof {
+    let proof_address = proof_pubkey(authority);
+    let account =
+        with_retries(|| rpc_client.get_account_with_commitment(&proof_address, commitment))
+            .await
+            .expect("Failed to get miner account")
+            .value
+            .expect("Miner account not found");
+    *Proof::try_from_bytes(&account.data).expect("Failed to parse miner account")
+}
+
 pub async fn get_clock_account(rpc_client: &RpcClient) -> Clock {
-    let data = rpc_client
-        .get_account_data(&sysvar::clock::ID)
+    let data = with_retries(|| rpc_client.get_account_data(&sysvar::clock::ID))
         .await
         .expect("Failed to get miner account");
     bincode::deserialize::<Clock>(&data).expect("Failed to deserialize clock")
@@ -41,6 +234,170 @@ pub fn proof_pubkey(authority: Pubkey) -> Pubkey {
 }
 
 #[cached]
-pub fn treasury_tokens_pubkey() -> Pubkey {
-    get_associated_token_address(&TREASURY_ADDRESS, &MINT_ADDRESS)
+pub fn ore_token_pubkey(owner: Pubkey) -> Pubkey {
+    get_associated_token_address(&owner, &MINT_ADDRESS)
+}
+
+// ORE token accounts seen to exist, so claims can skip checking for them again
+static KNOWN_TOKEN_ACCOUNTS: LazyLock<Mutex<HashSet<Pubkey>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Fetches the ORE token account of `owner`, remembering that it exists so
+/// later claims can skip checking for it again.
+pub async fn resolve_ata(
+    rpc_client: &RpcClient,
+    owner: Pubkey,
+) -> ClientResult<Option<UiTokenAccount>> {
+    let token_account = rpc_client
+        .get_token_account(&ore_token_pubkey(owner))
+        .await?;
+    if token_account.is_some() {
+        remember_ata(owner);
+    }
+    Ok(token_account)
+}
+
+/// Returns whether the ORE token account of `owner` is already known to exist.
+pub fn is_known_ata(owner: Pubkey) -> bool {
+    KNOWN_TOKEN_ACCOUNTS
+        .lock()
+        .unwrap()
+        .contains(&ore_token_pubkey(owner))
+}
+
+/// Records that the ORE token account of `owner` exists.
+pub fn remember_ata(owner: Pubkey) {
+    KNOWN_TOKEN_ACCOUNTS
+        .lock()
+        .unwrap()
+        .insert(ore_token_pubkey(owner));
+}
+
+#[cached]
+pub fn treasury_tokens_pubkey(treasury: Pubkey) -> Pubkey {
+    get_associated_token_address(&treasury, &MINT_ADDRESS)
+}
+
+/// Returns the hostname of this machine, or `unknown` if it cannot be determined.
+#[cfg(unix)]
+pub fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    let res = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+    if res != 0 {
+        return "unknown".into();
+    }
+    let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+#[cfg(not(unix))]
+pub fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_else(|_| "unknown".into())
+}
+
+// Human-readable names for keypairs, set with --keypair-alias
+static KEYPAIR_ALIASES: LazyLock<RwLock<HashMap<Pubkey, String>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Returns the alias of the given pubkey, if one is set, or the pubkey itself.
+pub fn format_pubkey(pubkey: &Pubkey) -> String {
+    match KEYPAIR_ALIASES.read().unwrap().get(pubkey) {
+        Some(alias) => alias.clone(),
+        None => pubkey.to_string(),
+    }
+}
+
+/// Loads the persisted keypair aliases stored next to the config file.
+/// If `alias` is set, it is assigned to `pubkey` and written back so it persists across sessions.
+pub fn load_keypair_aliases(config_file: Option<&str>, pubkey: Pubkey, alias: Option<String>) {
+    let path = config_file.map(keypair_aliases_path);
+    let mut aliases: HashMap<String, String> = path
+        .as_ref()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default();
+    if let Some(alias) = alias {
+        aliases.insert(pubkey.to_string(), alias);
+        if let Some(path) = path.as_ref() {
+            let res = serde_json::to_string_pretty(&aliases)
+                .map_err(std::io::Error::from)
+                .and_then(|data| std::fs::write(path, data));
+            if let Err(err) = res {
+                println!(
+                    "Failed to save keypair alias to {}: {:?}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+    }
+
+    let mut lock = KEYPAIR_ALIASES.write().unwrap();
+    for (pubkey, alias) in aliases {
+        if let Ok(pubkey) = pubkey.parse() {
+            lock.insert(pubkey, alias);
+        }
+    }
+}
+
+fn keypair_aliases_path(config_file: &str) -> PathBuf {
+    Path::new(config_file).with_file_name("ore-keypair-aliases.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_program::native_token::LAMPORTS_PER_SOL;
+
+    use super::*;
+
+    #[test]
+    fn parses_ore_amounts() {
+        assert_eq!(parse_ore_amount("1.5"), Ok(ore::ONE_ORE * 3 / 2));
+        assert_eq!(parse_ore_amount("1_000"), Ok(ore::ONE_ORE * 1_000));
+        assert_eq!(parse_ore_amount("2 ORE"), Ok(ore::ONE_ORE * 2));
+        assert_eq!(parse_ore_amount("2ore"), Ok(ore::ONE_ORE * 2));
+        assert_eq!(parse_ore_amount(".5"), Ok(ore::ONE_ORE / 2));
+        assert_eq!(parse_ore_amount("1."), Ok(ore::ONE_ORE));
+        assert_eq!(parse_ore_amount("0"), Ok(0));
+        assert_eq!(parse_ore_amount("0.000000001"), Ok(1));
+    }
+
+    #[test]
+    fn rejects_invalid_ore_amounts() {
+        assert!(parse_ore_amount(".").is_err());
+        assert!(parse_ore_amount("").is_err());
+        assert!(parse_ore_amount("ORE").is_err());
+        assert!(parse_ore_amount("-1").is_err());
+        assert!(parse_ore_amount("1.2.3").is_err());
+        assert!(parse_ore_amount("abc").is_err());
+    }
+
+    #[test]
+    fn rejects_too_many_decimals() {
+        assert!(parse_ore_amount("0.0000000001").is_err());
+    }
+
+    #[test]
+    fn rejects_overflowing_ore_amounts() {
+        assert!(parse_ore_amount(&u64::MAX.to_string()).is_err());
+        assert!(parse_ore_amount("18446744073.709551616").is_err());
+    }
+
+    #[test]
+    fn rejects_zero_claim_amounts() {
+        assert!(parse_nonzero_ore_amount("0").is_err());
+        assert!(parse_nonzero_ore_amount("0.0").is_err());
+        assert_eq!(parse_nonzero_ore_amount("1"), Ok(ore::ONE_ORE));
+    }
+
+    #[test]
+    fn parses_sol_amounts() {
+        assert_eq!(parse_sol_amount("0.5"), Ok(LAMPORTS_PER_SOL / 2));
+        assert_eq!(parse_sol_amount("1 SOL"), Ok(LAMPORTS_PER_SOL));
+        assert!(parse_sol_amount("0").is_err());
+        assert!(parse_sol_amount("-1").is_err());
+        assert!(parse_sol_amount("NaN").is_err());
+        assert!(parse_sol_amount("inf").is_err());
+        assert!(parse_sol_amount("0.0000000001").is_err());
+    }
 }
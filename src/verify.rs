@@ -0,0 +1,114 @@
+use std::str::FromStr;
+
+use ore::instruction::{MineArgs, OreInstruction};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{
+    keccak::{hashv, Hash as KeccakHash},
+    signature::Signature,
+};
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::{utils::get_treasury, Miner, VerifyArgs};
+
+impl Miner {
+    pub async fn verify(&self, args: VerifyArgs) {
+        let Ok(signature) = Signature::from_str(&args.signature) else {
+            println!("Invalid signature: {:?}", args.signature);
+            return;
+        };
+        let tx = match self
+            .rpc_client
+            .get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: None,
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await
+        {
+            Ok(tx) => tx,
+            Err(err) => {
+                println!("Failed to fetch transaction: {:?}", err);
+                return;
+            }
+        };
+        let status_err = tx
+            .transaction
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.err.clone());
+        let Some(decoded_tx) = tx.transaction.transaction.decode() else {
+            println!("FAIL: Could not decode transaction");
+            return;
+        };
+
+        // Find the ore instruction, skipping compute budget instructions
+        let account_keys = decoded_tx.message.static_account_keys();
+        let Some(ix) = decoded_tx.message.instructions().iter().find(|ix| {
+            account_keys
+                .get(ix.program_id_index as usize)
+                .eq(&Some(&ore::ID))
+        }) else {
+            println!("FAIL: Transaction has no ore instruction");
+            return;
+        };
+        if ix.data.first().ne(&Some(&(OreInstruction::Mine as u8))) {
+            println!("FAIL: Ore instruction is not a mine instruction");
+            return;
+        }
+        let Ok(mine_args) = MineArgs::try_from_bytes(&ix.data[1..]) else {
+            println!("FAIL: Could not decode mine instruction data");
+            return;
+        };
+        let Some(authority) = ix
+            .accounts
+            .first()
+            .and_then(|i| account_keys.get(*i as usize))
+        else {
+            println!("FAIL: Mine instruction has no signer");
+            return;
+        };
+        let hash: KeccakHash = mine_args.hash.into();
+        let nonce = u64::from_le_bytes(mine_args.nonce);
+        println!("Signer: {}", authority);
+        println!("Hash: {}", hash);
+        println!("Nonce: {}", nonce);
+        match status_err.as_ref() {
+            Some(err) => println!("Status: failed ({})", err),
+            None => println!("Status: succeeded"),
+        }
+
+        // Check the hash against the challenge and difficulty
+        let Ok(challenge) = KeccakHash::from_str(&args.challenge) else {
+            println!("Invalid challenge: {:?}", args.challenge);
+            return;
+        };
+        let difficulty: KeccakHash = get_treasury(&self.rpc_client, &self.addresses.treasury)
+            .await
+            .difficulty
+            .into();
+        let expected = hashv(&[
+            challenge.as_ref(),
+            authority.as_ref(),
+            nonce.to_le_bytes().as_slice(),
+        ]);
+        let hash_valid = expected.eq(&hash);
+        let difficulty_met = hash.le(&difficulty);
+        println!("Challenge: {}", challenge);
+        println!(
+            "Hash matches challenge: {}",
+            if hash_valid { "yes" } else { "no" }
+        );
+        println!(
+            "Hash meets difficulty: {}",
+            if difficulty_met { "yes" } else { "no" }
+        );
+        if hash_valid && difficulty_met {
+            println!("PASS");
+        } else {
+            println!("FAIL");
+        }
+    }
+}
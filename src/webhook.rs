@@ -0,0 +1,14 @@
+/// Posts an alert message to the `--notify-webhook` URL.
+/// The body is `{"text": "<message>"}`, which Slack and Discord-compatible webhooks accept.
+pub async fn notify_webhook(http_client: &reqwest::Client, url: &str, message: &str) {
+    let body = serde_json::json!({ "text": message });
+    match http_client.post(url).json(&body).send().await {
+        Ok(res) if !res.status().is_success() => {
+            println!("Webhook returned status {}", res.status());
+        }
+        Ok(_) => {}
+        Err(err) => {
+            println!("Failed to notify webhook: {:?}", err);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use solana_program::{native_token::lamports_to_sol, system_instruction};
+use solana_sdk::signature::Signer;
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+
+use crate::{Miner, WrapSolArgs};
+
+impl Miner {
+    pub async fn wrap_sol(&self, args: WrapSolArgs) {
+        let signer = self.signer();
+        let lamports = args.amount;
+        let wsol_address =
+            get_associated_token_address(&signer.pubkey(), &spl_token::native_mint::id());
+        let ixs = [
+            create_associated_token_account_idempotent(
+                &signer.pubkey(),
+                &signer.pubkey(),
+                &spl_token::native_mint::id(),
+                &spl_token::id(),
+            ),
+            system_instruction::transfer(&signer.pubkey(), &wsol_address, lamports),
+            spl_token::instruction::sync_native(&spl_token::id(), &wsol_address)
+                .expect("Failed to build sync_native instruction"),
+        ];
+        println!(
+            "Wrapping {} SOL into {}...",
+            lamports_to_sol(lamports),
+            wsol_address
+        );
+        match self.send_and_confirm(&ixs, true, false).await {
+            Ok(sig) => {
+                println!("Wrapped {} SOL", lamports_to_sol(lamports));
+                println!("{:?}", sig);
+            }
+            Err(err) => println!("Error: {:?}", err),
+        }
+    }
+
+    pub async fn unwrap_sol(&self) {
+        let signer = self.signer();
+        let wsol_address =
+            get_associated_token_address(&signer.pubkey(), &spl_token::native_mint::id());
+        if !matches!(
+            self.rpc_client.get_token_account(&wsol_address).await,
+            Ok(Some(_))
+        ) {
+            println!("No wSOL account found at {}", wsol_address);
+            return;
+        }
+        let ix = spl_token::instruction::close_account(
+            &spl_token::id(),
+            &wsol_address,
+            &signer.pubkey(),
+            &signer.pubkey(),
+            &[],
+        )
+        .expect("Failed to build close_account instruction");
+        println!("Unwrapping {}...", wsol_address);
+        match self.send_and_confirm(&[ix], true, false).await {
+            Ok(sig) => {
+                println!("Unwrapped all wSOL");
+                println!("{:?}", sig);
+            }
+            Err(err) => println!("Error: {:?}", err),
+        }
+    }
+}